@@ -0,0 +1,70 @@
+use crate::{ray::Ray, vec3::Point3d};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub minimum: Point3d,
+    pub maximum: Point3d,
+}
+
+impl Aabb {
+    pub fn new(a: Point3d, b: Point3d) -> Self {
+        Self {
+            minimum: a,
+            maximum: b,
+        }
+    }
+
+    pub fn hit(&self, r: &Ray, mut t_min: f64, mut t_max: f64) -> bool {
+        let origin = r.origin();
+        let direction = r.direction();
+
+        for a in 0..3 {
+            let inv_d = 1.0 / direction[a];
+            let mut t0 = (self.minimum[a] - origin[a]) * inv_d;
+            let mut t1 = (self.maximum[a] - origin[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max <= t_min {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn centroid(&self) -> Point3d {
+        0.5 * (self.minimum + self.maximum)
+    }
+
+    pub fn surrounding_box(box0: &Aabb, box1: &Aabb) -> Aabb {
+        let small = Point3d::new(
+            box0.minimum.x.min(box1.minimum.x),
+            box0.minimum.y.min(box1.minimum.y),
+            box0.minimum.z.min(box1.minimum.z),
+        );
+        let big = Point3d::new(
+            box0.maximum.x.max(box1.maximum.x),
+            box0.maximum.y.max(box1.maximum.y),
+            box0.maximum.z.max(box1.maximum.z),
+        );
+        Aabb::new(small, big)
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let d = self.maximum - self.minimum;
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    pub fn longest_axis(&self) -> usize {
+        let extent = self.maximum - self.minimum;
+        if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::cmp::Ordering;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    hittable_list::HittableList,
+    ray::Ray,
+    sampler::Sampler,
+};
+
+/// Objects without a bounding box, like infinite planes, are kept outside
+/// the tree and tested against every ray. An empty list hits nothing.
+pub struct BvhNode {
+    root: Option<Node>,
+    unbounded: Vec<Box<dyn Hittable>>,
+}
+
+struct Node {
+    left: Box<dyn Hittable>,
+    right: Option<Box<dyn Hittable>>,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    pub fn new(list: HittableList) -> Self {
+        let mut bounded = vec![];
+        let mut unbounded = vec![];
+        for object in list.objects {
+            match object.bounding_box() {
+                Some(bbox) => bounded.push((bbox, object)),
+                None => unbounded.push(object),
+            }
+        }
+
+        Self {
+            root: (!bounded.is_empty()).then(|| Node::build(bounded)),
+            unbounded,
+        }
+    }
+}
+
+impl Node {
+    fn build(mut objects: Vec<(Aabb, Box<dyn Hittable>)>) -> Self {
+        // split along the axis where the object centers are spread the most
+        let centroid_bounds = objects
+            .iter()
+            .map(|(bbox, _)| Aabb::new(bbox.centroid(), bbox.centroid()))
+            .reduce(|a, b| Aabb::surrounding_box(&a, &b))
+            .unwrap();
+        let axis = centroid_bounds.longest_axis();
+
+        match objects.len() {
+            1 => {
+                let (bbox, object) = objects.pop().unwrap();
+                Self {
+                    left: object,
+                    right: None,
+                    bbox,
+                }
+            }
+            2 => {
+                let (box1, right) = objects.pop().unwrap();
+                let (box0, left) = objects.pop().unwrap();
+                Self {
+                    left,
+                    right: Some(right),
+                    bbox: Aabb::surrounding_box(&box0, &box1),
+                }
+            }
+            len => {
+                objects.sort_by(|(a, _), (b, _)| {
+                    a.centroid()[axis]
+                        .partial_cmp(&b.centroid()[axis])
+                        .unwrap_or(Ordering::Equal)
+                });
+                let rest = objects.split_off(len / 2);
+                let left = Self::build(objects);
+                let right = Self::build(rest);
+                let bbox = Aabb::surrounding_box(&left.bbox, &right.bbox);
+                Self {
+                    left: Box::new(left),
+                    right: Some(Box::new(right)),
+                    bbox,
+                }
+            }
+        }
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut Sampler) -> Option<HitRecord<'_>> {
+        let mut closest_so_far = t_max;
+        let mut temp_rec = None;
+        for object in self.unbounded.iter() {
+            if let Some(result) = object.hit(r, t_min, closest_so_far, sampler) {
+                closest_so_far = result.t;
+                temp_rec = Some(result);
+            }
+        }
+
+        self.root
+            .as_ref()
+            .and_then(|root| root.hit(r, t_min, closest_so_far, sampler))
+            .or(temp_rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+        self.root.as_ref().map(|root| root.bbox)
+    }
+}
+
+impl Hittable for Node {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut Sampler) -> Option<HitRecord<'_>> {
+        if !self.bbox.hit(r, t_min, t_max) {
+            return None;
+        }
+
+        let hit_left = self.left.hit(r, t_min, t_max, sampler);
+        let closest = hit_left.as_ref().map_or(t_max, |rec| rec.t);
+        let hit_right = self
+            .right
+            .as_ref()
+            .and_then(|right| right.hit(r, t_min, closest, sampler));
+
+        hit_right.or(hit_left)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     ray::Ray,
+    sampler::Sampler,
     utils::degrees_to_radians,
     vec3::{Point3d, Vec3d},
 };
@@ -11,17 +12,22 @@ pub struct Camera {
     vertical: Vec3d,
     u: Vec3d,
     v: Vec3d,
-    w: Vec3d,
     lens_radius: f64,
+    /// height of the image at distance 1
+    viewport_height: f64,
+    /// rays are spread over the time the shutter is open
+    time0: f64,
+    time1: f64,
 }
 
 impl Camera {
-    pub fn get_ray(&self, u: f64, v: f64) -> Ray {
-        let rd = self.lens_radius * Vec3d::random_in_unit_disk();
+    pub fn get_ray(&self, u: f64, v: f64, sampler: &mut Sampler) -> Ray {
+        let rd = self.lens_radius * Vec3d::random_in_unit_disk(sampler);
         let offset = self.u * rd.x + self.v * rd.y;
         Ray::new(
             self.origin + offset,
             self.lower_left_corner + u * self.horizontal + v * self.vertical - self.origin - offset,
+            sampler.random_range(self.time0, self.time1),
         )
     }
 
@@ -56,8 +62,24 @@ impl Camera {
             lower_left_corner,
             u,
             v,
-            w,
             lens_radius,
+            viewport_height,
+            time0: 0.0,
+            time1: 0.0,
         }
     }
+
+    /// How much wider the rays through one of `image_height` rows of pixels
+    /// get per unit of distance, see [`Ray::with_spread`].
+    pub fn pixel_spread(&self, image_height: usize) -> f64 {
+        self.viewport_height / image_height as f64
+    }
+
+    /// Opens the shutter at `time0` and closes it at `time1`, so that
+    /// moving objects blur. By default every ray is at time 0.
+    pub fn with_shutter(mut self, time0: f64, time1: f64) -> Self {
+        self.time0 = time0;
+        self.time1 = time1;
+        self
+    }
 }
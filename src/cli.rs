@@ -0,0 +1,387 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use ray_tracing_in_rust::environment::{Environment, EnvironmentMap};
+use ray_tracing_in_rust::gltf_scene::GltfScene;
+use ray_tracing_in_rust::scene_file::{load_scene, LoadedScene};
+use ray_tracing_in_rust::scenes::{builtin_scenes, find_builtin_scene, BuiltinScene};
+use ray_tracing_in_rust::sky::PhysicalSky;
+use ray_tracing_in_rust::{ImageFormat, ToneMapper, ToneMapping};
+
+const DEFAULT_SCENE: &str = "final";
+
+pub const USAGE: &str = "\
+Usage: ray-tracing-in-rust [OPTIONS] [SCENE]
+
+Renders one of the built-in scenes (default: final), or the scene described
+by SCENE if it is a path to a .toml scene file or a .gltf or .glb model.
+
+Options:
+  -w, --width <PIXELS>        image width
+      --height <PIXELS>       image height
+  -a, --aspect-ratio <RATIO>  width / height, e.g. 1.5, 3:2 or 16/9
+  -s, --spp <N>               samples per pixel
+  -d, --max-depth <N>         maximum number of bounces per ray
+      --seed <N>              seed for scene generation and sampling
+  -j, --threads <N>           number of render threads (default: all cores)
+  -o, --output <PATH>         output file (default: ./result/<SCENE>.png)
+  -f, --format <FORMAT>       png8, png16, ppm (binary), ppm-ascii, exr (half),
+                              exr32 or hdr (radiance)
+                              (default: from the output extension)
+      --environment <FILE>    light the scene with a latitude-longitude .hdr,
+                              .exr or png panorama instead of its own sky
+      --sun <ELEV[:AZIMUTH]>  light the scene with a physical sky, the sun at
+                              ELEV degrees above the horizon and AZIMUTH
+                              degrees from -z towards +x (default: 0)
+      --turbidity <T>         haziness of the --sun sky, 1.7 to 10 (default: 3)
+  -t, --tonemap <OPERATOR>    clamp, reinhard, reinhard-extended, aces, hable or
+                              agx, for png and ppm output (default: clamp)
+  -e, --exposure <STOPS>      exposure adjustment in stops (default: 0)
+      --white-point <VALUE>   radiance mapped to white by reinhard-extended
+                              (default: 4)
+  -l, --list                  list the built-in scenes and exit
+  -h, --help                  print this help and exit";
+
+pub enum Command {
+    Help,
+    List,
+    Render(Box<Options>),
+}
+
+pub enum SceneSource {
+    Builtin(&'static BuiltinScene),
+    File(LoadedScene),
+}
+
+pub struct Options {
+    pub name: String,
+    pub scene: SceneSource,
+    pub image_width: usize,
+    pub image_height: usize,
+    pub aspect_ratio: f64,
+    pub samples_per_pixel: i32,
+    pub max_depth: i32,
+    pub seed: Option<u64>,
+    pub threads: Option<usize>,
+    pub output: PathBuf,
+    pub format: ImageFormat,
+    pub tone_mapping: ToneMapping,
+    pub environment: Option<Box<dyn Environment>>,
+}
+
+#[derive(Default)]
+struct RawOptions {
+    scene: Option<String>,
+    width: Option<usize>,
+    height: Option<usize>,
+    aspect_ratio: Option<f64>,
+    samples_per_pixel: Option<i32>,
+    max_depth: Option<i32>,
+    seed: Option<u64>,
+    threads: Option<usize>,
+    output: Option<PathBuf>,
+    format: Option<ImageFormat>,
+    tone_mapper: Option<ToneMapper>,
+    exposure: Option<f64>,
+    white_point: Option<f64>,
+    environment: Option<PathBuf>,
+    sun: Option<(f64, f64)>,
+    turbidity: Option<f64>,
+}
+
+fn parse_value<T>(flag: &str, value: Option<String>) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = value.ok_or_else(|| format!("{} expects a value", flag))?;
+    value
+        .parse()
+        .map_err(|e| format!("invalid value '{}' for {}: {}", value, flag, e))
+}
+
+fn parse_positive<T>(flag: &str, value: Option<String>) -> Result<T, String>
+where
+    T: FromStr + PartialOrd + Default + Copy,
+    T::Err: Display,
+{
+    let parsed: T = parse_value(flag, value)?;
+    // nan is not greater either
+    if parsed.partial_cmp(&T::default()) != Some(Ordering::Greater) {
+        return Err(format!("{} must be greater than zero", flag));
+    }
+    Ok(parsed)
+}
+
+fn parse_positive_finite(flag: &str, value: Option<String>) -> Result<f64, String> {
+    let parsed: f64 = parse_positive(flag, value)?;
+    if !parsed.is_finite() {
+        return Err(format!("{} must be finite", flag));
+    }
+    Ok(parsed)
+}
+
+fn parse_aspect_ratio(flag: &str, value: Option<String>) -> Result<f64, String> {
+    let value = value.ok_or_else(|| format!("{} expects a value", flag))?;
+    let invalid = || {
+        format!(
+            "invalid value '{}' for {}: expected a number or WIDTH:HEIGHT",
+            value, flag
+        )
+    };
+
+    let ratio = match value.split_once([':', '/']) {
+        Some((w, h)) => {
+            let w: f64 = w.trim().parse().map_err(|_| invalid())?;
+            let h: f64 = h.trim().parse().map_err(|_| invalid())?;
+            w / h
+        }
+        None => value.parse().map_err(|_| invalid())?,
+    };
+    if !ratio.is_finite() || ratio <= 0.0 {
+        return Err(invalid());
+    }
+    Ok(ratio)
+}
+
+fn parse_sun(flag: &str, value: Option<String>) -> Result<(f64, f64), String> {
+    let value = value.ok_or_else(|| format!("{} expects a value", flag))?;
+    let invalid = || {
+        format!(
+            "invalid value '{}' for {}: expected ELEVATION or ELEVATION:AZIMUTH in degrees",
+            value, flag
+        )
+    };
+
+    let (elevation, azimuth) = match value.split_once([':', ',']) {
+        Some((e, a)) => (
+            e.trim().parse::<f64>().map_err(|_| invalid())?,
+            a.trim().parse::<f64>().map_err(|_| invalid())?,
+        ),
+        None => (value.trim().parse::<f64>().map_err(|_| invalid())?, 0.0),
+    };
+    if !(-90.0..=90.0).contains(&elevation) || !azimuth.is_finite() {
+        return Err(invalid());
+    }
+    Ok((elevation, azimuth))
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut raw = RawOptions::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // accept both `--flag value` and `--flag=value`
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = || inline.clone().or_else(|| args.next());
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-l" | "--list" => return Ok(Command::List),
+            "-w" | "--width" => raw.width = Some(parse_positive(&flag, value())?),
+            "--height" => raw.height = Some(parse_positive(&flag, value())?),
+            "-a" | "--aspect-ratio" => raw.aspect_ratio = Some(parse_aspect_ratio(&flag, value())?),
+            "-s" | "--spp" => raw.samples_per_pixel = Some(parse_positive(&flag, value())?),
+            "-d" | "--max-depth" => raw.max_depth = Some(parse_positive(&flag, value())?),
+            "--seed" => raw.seed = Some(parse_value(&flag, value())?),
+            "-j" | "--threads" => raw.threads = Some(parse_positive(&flag, value())?),
+            "-o" | "--output" => {
+                raw.output = Some(PathBuf::from(parse_value::<String>(&flag, value())?))
+            }
+            "-f" | "--format" => raw.format = Some(parse_value(&flag, value())?),
+            "--environment" => {
+                raw.environment = Some(PathBuf::from(parse_value::<String>(&flag, value())?))
+            }
+            "--sun" => raw.sun = Some(parse_sun(&flag, value())?),
+            "--turbidity" => {
+                let turbidity: f64 = parse_value(&flag, value())?;
+                if !(1.7..=10.0).contains(&turbidity) {
+                    return Err(format!("{} must be between 1.7 and 10", flag));
+                }
+                raw.turbidity = Some(turbidity)
+            }
+            "-t" | "--tonemap" => raw.tone_mapper = Some(parse_value(&flag, value())?),
+            "-e" | "--exposure" => {
+                let exposure: f64 = parse_value(&flag, value())?;
+                if !exposure.is_finite() {
+                    return Err(format!("{} must be a finite number", flag));
+                }
+                raw.exposure = Some(exposure)
+            }
+            "--white-point" => raw.white_point = Some(parse_positive_finite(&flag, value())?),
+            _ if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(format!("unknown option '{}'", flag))
+            }
+            _ => {
+                if let Some(scene) = &raw.scene {
+                    return Err(format!(
+                        "unexpected argument '{}' after scene '{}'",
+                        arg, scene
+                    ));
+                }
+                raw.scene = Some(arg);
+            }
+        }
+    }
+
+    raw.resolve()
+        .map(|options| Command::Render(Box::new(options)))
+}
+
+impl RawOptions {
+    fn resolve(self) -> Result<Options, String> {
+        let name = self.scene.as_deref().unwrap_or(DEFAULT_SCENE);
+        let path = Path::new(name);
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        let loaded = match extension.as_deref() {
+            Some("toml") => Some(load_scene(path).map_err(|e| e.to_string())),
+            Some("gltf" | "glb") => Some(
+                GltfScene::load(path)
+                    .map(GltfScene::into_loaded_scene)
+                    .map_err(|e| e.to_string()),
+            ),
+            _ => None,
+        };
+        let (scene, scene_name) = if let Some(loaded) = loaded {
+            let loaded = loaded.map_err(|e| format!("cannot load '{}': {}", name, e))?;
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            (SceneSource::File(loaded), stem.into_owned())
+        } else {
+            let builtin = find_builtin_scene(name).ok_or_else(|| {
+                format!(
+                    "unknown scene '{}', expected one of: {}, a .toml scene file or a .gltf or .glb model",
+                    name,
+                    builtin_scenes()
+                        .iter()
+                        .map(|scene| scene.name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+            (SceneSource::Builtin(builtin), builtin.name.to_string())
+        };
+        let defaults = match &scene {
+            SceneSource::Builtin(builtin) => builtin.settings,
+            SceneSource::File(loaded) => loaded.settings,
+        };
+
+        let (image_width, image_height, aspect_ratio) =
+            match (self.width, self.height, self.aspect_ratio) {
+                (Some(_), Some(_), Some(_)) => {
+                    return Err(
+                        "--width, --height and --aspect-ratio cannot all be given at once"
+                            .to_string(),
+                    )
+                }
+                (Some(w), Some(h), None) => (w, h, w as f64 / h as f64),
+                (Some(w), None, aspect) => {
+                    let aspect = aspect.unwrap_or(defaults.aspect_ratio);
+                    (w, (w as f64 / aspect) as usize, aspect)
+                }
+                (None, Some(h), aspect) => {
+                    let aspect = aspect.unwrap_or(defaults.aspect_ratio);
+                    ((h as f64 * aspect) as usize, h, aspect)
+                }
+                (None, None, aspect) => {
+                    let aspect = aspect.unwrap_or(defaults.aspect_ratio);
+                    let w = defaults.image_width;
+                    (w, (w as f64 / aspect) as usize, aspect)
+                }
+            };
+        if image_width < 2 || image_height < 2 {
+            return Err(format!(
+                "image must be at least 2x2 pixels, got {}x{}",
+                image_width, image_height
+            ));
+        }
+
+        let output = self
+            .output
+            .unwrap_or_else(|| PathBuf::from(format!("./result/{}.png", scene_name)));
+        let format = match self.format.or_else(|| ImageFormat::from_path(&output)) {
+            Some(format) => format,
+            None => {
+                return Err(format!(
+                "cannot tell the image format of '{}', use a .png, .ppm, .exr or .hdr extension or --format",
+                output.display()
+            ))
+            }
+        };
+
+        let mut tone_mapping = ToneMapping::new(self.tone_mapper.unwrap_or(ToneMapper::Clamp))
+            .with_exposure(self.exposure.unwrap_or(0.0));
+        if let Some(white_point) = self.white_point {
+            tone_mapping = tone_mapping.with_white_point(white_point);
+        }
+
+        let environment: Option<Box<dyn Environment>> = match (&self.environment, self.sun) {
+            (Some(_), Some(_)) => {
+                return Err("--environment and --sun cannot be given at once".to_string())
+            }
+            (Some(path), None) => {
+                Some(Box::new(EnvironmentMap::load(path).map_err(|e| {
+                    format!("cannot load '{}': {}", path.display(), e)
+                })?))
+            }
+            (None, Some((elevation, azimuth))) => Some(Box::new(
+                PhysicalSky::new(elevation, azimuth).with_turbidity(self.turbidity.unwrap_or(3.0)),
+            )),
+            (None, None) => None,
+        };
+        if self.turbidity.is_some() && self.sun.is_none() {
+            return Err("--turbidity needs --sun".to_string());
+        }
+
+        Ok(Options {
+            name: scene_name,
+            scene,
+            image_width,
+            image_height,
+            aspect_ratio,
+            samples_per_pixel: self.samples_per_pixel.unwrap_or(defaults.samples_per_pixel),
+            max_depth: self.max_depth.unwrap_or(defaults.max_depth),
+            seed: self.seed,
+            threads: self.threads,
+            output,
+            format,
+            tone_mapping,
+            environment,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn rejects_non_finite_white_points() {
+        for value in ["NaN", "inf", "-inf", "0", "-1"] {
+            assert!(parse(&["--white-point", value]).is_err(), "{}", value);
+        }
+        assert!(parse(&["--white-point", "2.5"]).is_ok());
+    }
+
+    #[test]
+    fn rejects_non_positive_counts() {
+        assert!(parse(&["--spp", "0"]).is_err());
+        assert!(parse(&["--max-depth", "-3"]).is_err());
+        assert!(parse(&["--width", "1"]).is_err());
+        assert!(parse(&["--spp", "4", "--max-depth", "3"]).is_ok());
+    }
+}
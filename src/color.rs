@@ -1,46 +1,65 @@
-use crate::{utils::clamp, vec3::Color3d};
+use crate::{tonemap::ToneMapping, vec3::Color3d};
 use std::io;
 
-// pub fn write_color(fp: &mut impl io::Write, color: Color3d) -> io::Result<()> {
-//     fp.write(
-//         format!(
-//             "{} {} {}\n",
-//             (255.999 * color.x) as u8,
-//             (255.999 * color.y) as u8,
-//             (255.999 * color.z) as u8,
-//         )
-//         .as_bytes(),
-//     )?;
+/// The srgb transfer function, `x` is a linear value in `[0, 1]`.
+pub fn linear_to_srgb(x: f64) -> f64 {
+    if x <= 0.0031308 {
+        12.92 * x
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
 
-//     Ok(())
-// }
+/// Inverse of [`linear_to_srgb`].
+pub fn srgb_to_linear(x: f64) -> f64 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
 
-pub fn write_color(
-    fp: &mut impl io::Write,
+/// Averages the summed samples of a pixel, tone maps them and encodes them
+/// as srgb for display, every channel of the result is in `[0, 1]`.
+pub fn display_color(
     color: Color3d,
     samples_per_pixel: i32,
-) -> io::Result<()> {
-    let mut r = color.x;
-    let mut g = color.y;
-    let mut b = color.z;
+    tone_mapping: &ToneMapping,
+) -> Color3d {
+    let scale = 1.0 / samples_per_pixel.max(1) as f64;
+    let c = tone_mapping.apply(scale * color);
+
+    Color3d::new(
+        linear_to_srgb(c.x),
+        linear_to_srgb(c.y),
+        linear_to_srgb(c.z),
+    )
+}
 
-    let scale = 1.0 / samples_per_pixel as f64;
-    // r *= scale;
-    // g *= scale;
-    // b *= scale;
-    r = (scale * r).sqrt();
-    g = (scale * g).sqrt();
-    b = (scale * b).sqrt();
+pub fn to_rgb8(color: Color3d, samples_per_pixel: i32, tone_mapping: &ToneMapping) -> [u8; 3] {
+    let c = display_color(color, samples_per_pixel, tone_mapping);
+    [
+        (255.0 * c.x).round() as u8,
+        (255.0 * c.y).round() as u8,
+        (255.0 * c.z).round() as u8,
+    ]
+}
 
-    fp.write(
-        format!(
-            "{} {} {}\n",
-            256.0 * clamp(r, 0.0, 0.999),
-            256.0 * clamp(g, 0.0, 0.999),
-            256.0 * clamp(b, 0.0, 0.999),
-        )
-        .as_bytes(),
-    )?;
+pub fn to_rgb16(color: Color3d, samples_per_pixel: i32, tone_mapping: &ToneMapping) -> [u16; 3] {
+    let c = display_color(color, samples_per_pixel, tone_mapping);
+    [
+        (65535.0 * c.x).round() as u16,
+        (65535.0 * c.y).round() as u16,
+        (65535.0 * c.z).round() as u16,
+    ]
+}
 
-    Ok(())
+pub fn write_color(
+    fp: &mut impl io::Write,
+    color: Color3d,
+    samples_per_pixel: i32,
+    tone_mapping: &ToneMapping,
+) -> io::Result<()> {
+    let [r, g, b] = to_rgb8(color, samples_per_pixel, tone_mapping);
+    writeln!(fp, "{} {} {}", r, g, b)
 }
@@ -0,0 +1,90 @@
+//! Fog, smoke and other participating media of the same density throughout.
+
+use std::borrow::Borrow;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    sampler::Sampler,
+};
+
+/// The inside of `boundary`, filled with a medium that scatters rays by its
+/// `phase` material, usually [`Isotropic`](crate::material::Isotropic). A
+/// ray travels an exponentially distributed distance through it before it
+/// scatters, `1 / density` on average, and passes through if that is beyond
+/// where it leaves. The boundary must be closed and convex, like a sphere
+/// or a box; its own material is ignored.
+pub struct ConstantMedium<H, M>
+where
+    H: Hittable,
+    M: Material,
+{
+    pub boundary: H,
+    pub phase: M,
+    neg_inv_density: f64,
+}
+
+impl<H: Hittable, M: Material> ConstantMedium<H, M> {
+    pub fn new(boundary: H, density: f64, phase: M) -> Self {
+        assert!(
+            density > 0.0 && density.is_finite(),
+            "the density of a medium must be greater than 0, got {}",
+            density
+        );
+        Self {
+            boundary,
+            phase,
+            neg_inv_density: -1.0 / density,
+        }
+    }
+}
+
+impl<H: Hittable, M: Material> Hittable for ConstantMedium<H, M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        sampler: &mut Sampler,
+    ) -> Option<HitRecord<'_>> {
+        // where the line of the ray enters and leaves, even behind its origin
+        let enter = self
+            .boundary
+            .hit(ray, f64::NEG_INFINITY, f64::INFINITY, sampler)?
+            .t;
+        let leave = self
+            .boundary
+            .hit(ray, enter + 0.0001, f64::INFINITY, sampler)?
+            .t;
+
+        let enter = enter.max(t_min).max(0.0);
+        let leave = leave.min(t_max);
+        if enter >= leave {
+            return None;
+        }
+
+        let length = ray.direction().length();
+        let distance_inside = (leave - enter) * length;
+        // 1 - u is never 0, so the log stays finite
+        let hit_distance = self.neg_inv_density * (1.0 - sampler.random_double()).ln();
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        let t = enter + hit_distance / length;
+        // the normal means nothing inside, face it towards the ray
+        Some(HitRecord::new_with_face_normal(
+            t,
+            ray.at(t),
+            -ray.direction() / length,
+            self.phase.borrow(),
+            ray,
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
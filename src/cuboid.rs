@@ -0,0 +1,119 @@
+use std::borrow::Borrow;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    quad::QuadShape,
+    ray::Ray,
+    sampler::Sampler,
+    vec3::{Point3d, Vec3d},
+};
+
+/// Whether the box between the corners `a` and `b` has a volume, which it
+/// has unless they are equal on some axis.
+pub fn encloses_volume(a: &Point3d, b: &Point3d) -> bool {
+    (0..3).all(|axis| a[axis] != b[axis])
+}
+
+/// Axis aligned box between two opposite corners, made of six quads whose
+/// normals point out of the box.
+#[derive(Clone)]
+pub struct Cuboid<M>
+where
+    M: Material,
+{
+    sides: [QuadShape; 6],
+    bbox: Aabb,
+    pub material: M,
+}
+
+impl<M: Material> Cuboid<M> {
+    /// Panics unless the corners `a` and `b` [enclose a
+    /// volume](encloses_volume).
+    pub fn new(a: Point3d, b: Point3d, m: M) -> Self {
+        assert!(
+            encloses_volume(&a, &b),
+            "the corners of a box must differ on every axis, got {:?} and {:?}",
+            a,
+            b
+        );
+        let min = Point3d::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+        let max = Point3d::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+
+        let dx = Vec3d::new(max.x - min.x, 0.0, 0.0);
+        let dy = Vec3d::new(0.0, max.y - min.y, 0.0);
+        let dz = Vec3d::new(0.0, 0.0, max.z - min.z);
+
+        let sides = [
+            // front, right, back, left, top and bottom
+            QuadShape::new(Point3d::new(min.x, min.y, max.z), dx, dy),
+            QuadShape::new(Point3d::new(max.x, min.y, max.z), -dz, dy),
+            QuadShape::new(Point3d::new(max.x, min.y, min.z), -dx, dy),
+            QuadShape::new(Point3d::new(min.x, min.y, min.z), dz, dy),
+            QuadShape::new(Point3d::new(min.x, max.y, max.z), dx, -dz),
+            QuadShape::new(Point3d::new(min.x, min.y, min.z), dx, dz),
+        ];
+
+        Self {
+            sides,
+            bbox: Aabb::new(min, max),
+            material: m,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Cuboid<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _sampler: &mut Sampler,
+    ) -> Option<HitRecord<'_>> {
+        let mut closest = None;
+        let mut closest_so_far = t_max;
+        for side in self.sides.iter() {
+            if let Some(hit) = side.intersect(ray, t_min, closest_so_far) {
+                closest_so_far = hit.0;
+                closest = Some((hit, side));
+            }
+        }
+
+        // every side has the whole of the texture
+        closest.map(|((t, point, outward_normal, (u, v)), side)| {
+            HitRecord::new_with_face_normal(t, point, outward_normal, self.material.borrow(), ray)
+                .with_uv(u, v)
+                .with_footprint(ray, side.uv_density())
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Diffuse;
+    use crate::vec3::Color3d;
+
+    #[test]
+    fn encloses_only_a_volume() {
+        let a = Point3d::new(1.0, 2.0, 3.0);
+        assert!(encloses_volume(&a, &Point3d::zero()));
+        assert!(!encloses_volume(&a, &Point3d::new(0.0, 2.0, 0.0)));
+        assert!(!encloses_volume(&a, &a));
+    }
+
+    #[test]
+    #[should_panic(expected = "must differ on every axis")]
+    fn rejects_flat_boxes() {
+        Cuboid::new(
+            Point3d::zero(),
+            Point3d::new(1.0, 0.0, 1.0),
+            Diffuse::new(Color3d::zero()),
+        );
+    }
+}
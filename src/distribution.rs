@@ -0,0 +1,126 @@
+//! Piecewise constant distributions, for drawing samples proportionally to a
+//! tabulated function such as the brightness of an image.
+
+/// A function over `[0, 1)` that is constant on each of `func.len()` pieces.
+#[derive(Debug, Clone)]
+pub struct Distribution1d {
+    func: Vec<f64>,
+    cdf: Vec<f64>,
+    integral: f64,
+}
+
+impl Distribution1d {
+    /// `func` must not be empty, negative values are treated as 0.
+    pub fn new(func: Vec<f64>) -> Self {
+        assert!(!func.is_empty(), "distribution needs at least one value");
+        let func: Vec<f64> = func
+            .into_iter()
+            .map(|f| if f.is_finite() { f.max(0.0) } else { 0.0 })
+            .collect();
+        let n = func.len();
+
+        let mut cdf = Vec::with_capacity(n + 1);
+        cdf.push(0.0);
+        for i in 0..n {
+            cdf.push(cdf[i] + func[i] / n as f64);
+        }
+        let integral = cdf[n];
+        if integral > 0.0 {
+            for c in cdf.iter_mut() {
+                *c /= integral;
+            }
+        } else {
+            // nothing to prefer, fall back to uniform
+            for (i, c) in cdf.iter_mut().enumerate() {
+                *c = i as f64 / n as f64;
+            }
+        }
+
+        Self {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.func.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.func.is_empty()
+    }
+
+    /// Integral of the function over `[0, 1)`.
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Maps a uniform `u` in `[0, 1)` to `(x, pdf, piece)`, where `x` is
+    /// distributed like the function and `piece` is the index it falls into.
+    pub fn sample(&self, u: f64) -> (f64, f64, usize) {
+        // last cdf entry not above u
+        let piece = self
+            .cdf
+            .partition_point(|&c| c <= u)
+            .saturating_sub(1)
+            .min(self.len() - 1);
+
+        let width = self.cdf[piece + 1] - self.cdf[piece];
+        let du = if width > 0.0 {
+            (u - self.cdf[piece]) / width
+        } else {
+            0.0
+        };
+        let x = ((piece as f64 + du) / self.len() as f64).min(1.0 - f64::EPSILON);
+        (x, self.pdf_of_piece(piece), piece)
+    }
+
+    fn pdf_of_piece(&self, piece: usize) -> f64 {
+        if self.integral > 0.0 {
+            self.func[piece] / self.integral
+        } else {
+            1.0
+        }
+    }
+
+    /// Density of [`Distribution1d::sample`] at `x`.
+    pub fn pdf(&self, x: f64) -> f64 {
+        let piece = ((x * self.len() as f64) as usize).min(self.len() - 1);
+        self.pdf_of_piece(piece)
+    }
+}
+
+/// A function over `[0, 1)²` that is constant on each cell of a `width` by
+/// `height` grid, sampled by picking a row first and a column within it.
+#[derive(Debug, Clone)]
+pub struct Distribution2d {
+    rows: Vec<Distribution1d>,
+    marginal: Distribution1d,
+}
+
+impl Distribution2d {
+    /// `func` holds `width * height` values row by row.
+    pub fn new(func: &[f64], width: usize, height: usize) -> Self {
+        assert_eq!(func.len(), width * height);
+        let rows: Vec<_> = func
+            .chunks_exact(width)
+            .map(|row| Distribution1d::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1d::new(rows.iter().map(|row| row.integral()).collect());
+        Self { rows, marginal }
+    }
+
+    /// Maps two uniform numbers to a point `(u, v)` and its density.
+    pub fn sample(&self, u0: f64, u1: f64) -> ((f64, f64), f64) {
+        let (v, pdf_v, row) = self.marginal.sample(u1);
+        let (u, pdf_u, _) = self.rows[row].sample(u0);
+        ((u, v), pdf_u * pdf_v)
+    }
+
+    /// Density of [`Distribution2d::sample`] at `(u, v)`.
+    pub fn pdf(&self, u: f64, v: f64) -> f64 {
+        let row = ((v * self.rows.len() as f64) as usize).min(self.rows.len() - 1);
+        self.marginal.pdf(v) * self.rows[row].pdf(u)
+    }
+}
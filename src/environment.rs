@@ -0,0 +1,241 @@
+//! What rays see when they leave the scene without hitting anything.
+
+use std::{f64::consts::PI, io, path::Path};
+
+use crate::{
+    distribution::Distribution2d,
+    image::Image,
+    sampler::Sampler,
+    utils::degrees_to_radians,
+    vec3::{Color3d, Vec3d},
+};
+
+pub trait Environment: Send + Sync {
+    /// Radiance arriving from `direction`, which need not be normalized.
+    fn radiance(&self, direction: &Vec3d) -> Color3d;
+
+    /// Picks a unit direction, preferring the bright parts of the
+    /// environment, and returns it with the radiance from there and the
+    /// density over solid angle it was picked with. Environments that return
+    /// `None` are only ever found by rays that happen to escape.
+    fn sample(&self, _sampler: &mut Sampler) -> Option<(Vec3d, Color3d, f64)> {
+        None
+    }
+
+    /// Density of [`Environment::sample`] picking `direction`.
+    fn pdf(&self, _direction: &Vec3d) -> f64 {
+        0.0
+    }
+}
+
+impl Environment for Box<dyn Environment> {
+    fn radiance(&self, direction: &Vec3d) -> Color3d {
+        self.as_ref().radiance(direction)
+    }
+
+    fn sample(&self, sampler: &mut Sampler) -> Option<(Vec3d, Color3d, f64)> {
+        self.as_ref().sample(sampler)
+    }
+
+    fn pdf(&self, direction: &Vec3d) -> f64 {
+        self.as_ref().pdf(direction)
+    }
+}
+
+/// The same color in every direction, black leaves the scene lit only by
+/// its lights.
+#[derive(Debug, Clone, Copy)]
+pub struct SolidColor {
+    pub color: Color3d,
+}
+
+impl SolidColor {
+    pub fn new(color: Color3d) -> Self {
+        Self { color }
+    }
+}
+
+impl Environment for SolidColor {
+    fn radiance(&self, _direction: &Vec3d) -> Color3d {
+        self.color
+    }
+}
+
+/// Blends from `bottom` straight down to `top` straight up.
+#[derive(Debug, Clone, Copy)]
+pub struct Gradient {
+    pub bottom: Color3d,
+    pub top: Color3d,
+}
+
+impl Gradient {
+    pub fn new(bottom: Color3d, top: Color3d) -> Self {
+        Self { bottom, top }
+    }
+
+    /// The white to blue sky of the book.
+    pub fn sky() -> Self {
+        Self::new(Color3d::only(1.0), Color3d::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::sky()
+    }
+}
+
+impl Environment for Gradient {
+    fn radiance(&self, direction: &Vec3d) -> Color3d {
+        let unit_direction = direction.unit_vector();
+        let t = 0.5 * (unit_direction.y + 1.0);
+
+        (1.0 - t) * self.bottom + t * self.top
+    }
+}
+
+/// Image based lighting from a latitude-longitude (equirectangular) image.
+/// The top row looks straight up, and the center of the image looks down
+/// `-z` before `rotation` is applied. Directions are importance sampled by
+/// the luminance of the pixels.
+#[derive(Debug, Clone)]
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color3d>,
+    distribution: Distribution2d,
+    /// rotation around the y axis in radians
+    rotation: f64,
+    intensity: f64,
+}
+
+impl EnvironmentMap {
+    /// `pixels` holds linear radiance row by row from the top.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color3d>) -> Self {
+        assert!(
+            width > 0 && height > 0 && pixels.len() == width * height,
+            "environment map needs {}x{} pixels, got {}",
+            width,
+            height,
+            pixels.len()
+        );
+        // rows near the poles cover less of the sphere
+        let mut func = Vec::with_capacity(width * height);
+        for (y, row) in pixels.chunks_exact(width).enumerate() {
+            let sin_theta = (PI * (y as f64 + 0.5) / height as f64).sin();
+            func.extend(row.iter().map(|c| luminance(*c) * sin_theta));
+        }
+        let distribution = Distribution2d::new(&func, width, height);
+
+        Self {
+            width,
+            height,
+            pixels,
+            distribution,
+            rotation: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    /// Loads a radiance `.hdr`, an OpenEXR `.exr` or a png image, see
+    /// [`Image::load`].
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let image = Image::load(path)?;
+        Ok(Self::new(image.width, image.height, image.pixels))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Turns the map around the y axis by `degrees`.
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees_to_radians(degrees);
+        self
+    }
+
+    /// Scales the radiance of every pixel.
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    fn texel(&self, x: usize, y: usize) -> Color3d {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Image coordinates in `[0, 1)` of `direction`.
+    pub fn direction_to_uv(&self, direction: &Vec3d) -> (f64, f64) {
+        let d = direction.unit_vector();
+        let phi = d.x.atan2(-d.z) - self.rotation;
+        let theta = d.y.clamp(-1.0, 1.0).acos();
+        ((phi / (2.0 * PI) + 0.5).rem_euclid(1.0), theta / PI)
+    }
+
+    /// Inverse of [`EnvironmentMap::direction_to_uv`], as a unit vector.
+    pub fn uv_to_direction(&self, u: f64, v: f64) -> Vec3d {
+        let phi = (u - 0.5) * 2.0 * PI + self.rotation;
+        let theta = v * PI;
+        Vec3d::new(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            -theta.sin() * phi.cos(),
+        )
+    }
+
+    /// Bilinear lookup, wrapping around horizontally.
+    pub fn lookup(&self, u: f64, v: f64) -> Color3d {
+        let x = u * self.width as f64 - 0.5;
+        let y = (v * self.height as f64 - 0.5).clamp(0.0, (self.height - 1) as f64);
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let (tx, ty) = (x - x0, y - y0);
+
+        let xa = (x0 as isize).rem_euclid(self.width as isize) as usize;
+        let xb = (xa + 1) % self.width;
+        let ya = y0 as usize;
+        let yb = (ya + 1).min(self.height - 1);
+
+        let top = (1.0 - tx) * self.texel(xa, ya) + tx * self.texel(xb, ya);
+        let bottom = (1.0 - tx) * self.texel(xa, yb) + tx * self.texel(xb, yb);
+        self.intensity * ((1.0 - ty) * top + ty * bottom)
+    }
+}
+
+impl Environment for EnvironmentMap {
+    fn radiance(&self, direction: &Vec3d) -> Color3d {
+        let (u, v) = self.direction_to_uv(direction);
+        self.lookup(u, v)
+    }
+
+    fn sample(&self, sampler: &mut Sampler) -> Option<(Vec3d, Color3d, f64)> {
+        let ((u, v), pdf) = self
+            .distribution
+            .sample(sampler.random_double(), sampler.random_double());
+        let sin_theta = (PI * v).sin();
+        if pdf <= 0.0 || sin_theta <= 0.0 {
+            return None;
+        }
+
+        // from density over the image to density over the sphere
+        let pdf = pdf / (2.0 * PI * PI * sin_theta);
+        Some((self.uv_to_direction(u, v), self.lookup(u, v), pdf))
+    }
+
+    fn pdf(&self, direction: &Vec3d) -> f64 {
+        let (u, v) = self.direction_to_uv(direction);
+        let sin_theta = (PI * v).sin();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        self.distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+fn luminance(c: Color3d) -> f64 {
+    0.2126 * c.x + 0.7152 * c.y + 0.0722 * c.z
+}
@@ -0,0 +1,323 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Cursor, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use exr::prelude::{
+    f16, AnyChannel, AnyChannels, Encoding, FlatSamples, Image, Layer, LayerAttributes, SmallVec,
+    WritableImage,
+};
+
+use crate::{
+    color::{to_rgb16, to_rgb8, write_color},
+    rgbe::write_hdr,
+    tonemap::ToneMapping,
+    vec3::Color3d,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// 8 bits per channel png
+    Png8,
+    /// 16 bits per channel png
+    Png16,
+    /// binary P6 ppm
+    Ppm,
+    /// plain text P3 ppm
+    PpmAscii,
+    /// OpenEXR with half floats
+    Exr,
+    /// OpenEXR with 32 bit floats
+    Exr32,
+    /// Radiance rgbe
+    Hdr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExrPrecision {
+    Half,
+    Float,
+}
+
+impl ImageFormat {
+    /// Picks the default format for the extension of `path`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "png" => Some(ImageFormat::Png8),
+            "ppm" => Some(ImageFormat::Ppm),
+            "exr" => Some(ImageFormat::Exr),
+            "hdr" => Some(ImageFormat::Hdr),
+            _ => None,
+        }
+    }
+
+    /// High dynamic range formats store linear radiance as is, the others
+    /// are tone mapped for display.
+    pub fn is_hdr(&self) -> bool {
+        matches!(
+            self,
+            ImageFormat::Exr | ImageFormat::Exr32 | ImageFormat::Hdr
+        )
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "png" | "png8" => Ok(ImageFormat::Png8),
+            "png16" => Ok(ImageFormat::Png16),
+            "ppm" | "p6" => Ok(ImageFormat::Ppm),
+            "ppm-ascii" | "p3" => Ok(ImageFormat::PpmAscii),
+            "exr" | "exr16" => Ok(ImageFormat::Exr),
+            "exr32" => Ok(ImageFormat::Exr32),
+            "hdr" => Ok(ImageFormat::Hdr),
+            _ => Err(format!(
+                "unknown image format '{}', expected png8, png16, ppm, ppm-ascii, exr, exr32 or hdr",
+                s
+            )),
+        }
+    }
+}
+
+/// Summed radiance samples of every pixel, stored row by row from the top of
+/// the image together with the number of samples each pixel received.
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color3d>,
+    samples: Vec<u32>,
+    tone_mapping: ToneMapping,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color3d::zero(); width * height],
+            samples: vec![0; width * height],
+            tone_mapping: ToneMapping::default(),
+        }
+    }
+
+    /// Tone mapping used by the low dynamic range formats, the high dynamic
+    /// range ones always store plain radiance.
+    pub fn tone_mapping(&self) -> &ToneMapping {
+        &self.tone_mapping
+    }
+
+    pub fn set_tone_mapping(&mut self, tone_mapping: ToneMapping) {
+        self.tone_mapping = tone_mapping;
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn add_sample(&mut self, x: usize, y: usize, color: Color3d) {
+        self.add_samples(x, y, color, 1);
+    }
+
+    /// Adds `count` samples whose sum is `color` to the pixel at `(x, y)`.
+    pub fn add_samples(&mut self, x: usize, y: usize, color: Color3d, count: u32) {
+        let index = y * self.width + x;
+        self.pixels[index] += color;
+        self.samples[index] += count;
+    }
+
+    /// Sum of the samples of the pixel at `(x, y)` and how many there are.
+    pub fn get(&self, x: usize, y: usize) -> (Color3d, u32) {
+        let index = y * self.width + x;
+        (self.pixels[index], self.samples[index])
+    }
+
+    /// Average radiance of the pixel at `(x, y)`.
+    pub fn pixel(&self, x: usize, y: usize) -> Color3d {
+        let (sum, count) = self.get(x, y);
+        if count == 0 {
+            Color3d::zero()
+        } else {
+            sum / count as f64
+        }
+    }
+
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = (Color3d, i32)> + '_> + '_ {
+        (0..self.height).map(move |y| {
+            (0..self.width).map(move |x| {
+                let (sum, count) = self.get(x, y);
+                (sum, count as i32)
+            })
+        })
+    }
+
+    pub fn write_ppm_ascii(&self, fp: &mut impl Write) -> io::Result<()> {
+        writeln!(fp, "P3\n{} {}\n255", self.width, self.height)?;
+        for row in self.rows() {
+            for (sum, count) in row {
+                write_color(fp, sum, count, &self.tone_mapping)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write_ppm(&self, fp: &mut impl Write) -> io::Result<()> {
+        write!(fp, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let mut data = Vec::with_capacity(3 * self.width * self.height);
+        for row in self.rows() {
+            for (sum, count) in row {
+                data.extend_from_slice(&to_rgb8(sum, count, &self.tone_mapping));
+            }
+        }
+        fp.write_all(&data)
+    }
+
+    pub fn write_png(&self, fp: &mut impl Write, sixteen_bit: bool) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(fp, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+
+        let mut data = Vec::with_capacity(6 * self.width * self.height);
+        if sixteen_bit {
+            encoder.set_depth(png::BitDepth::Sixteen);
+            for row in self.rows() {
+                for (sum, count) in row {
+                    for channel in to_rgb16(sum, count, &self.tone_mapping) {
+                        // png stores samples big-endian
+                        data.extend_from_slice(&channel.to_be_bytes());
+                    }
+                }
+            }
+        } else {
+            encoder.set_depth(png::BitDepth::Eight);
+            for row in self.rows() {
+                for (sum, count) in row {
+                    data.extend_from_slice(&to_rgb8(sum, count, &self.tone_mapping));
+                }
+            }
+        }
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    pub fn write_hdr(&self, fp: &mut impl Write) -> io::Result<()> {
+        let pixels = (0..self.height).flat_map(|y| (0..self.width).map(move |x| self.pixel(x, y)));
+        write_hdr(fp, self.width, self.height, pixels)
+    }
+
+    pub fn write_exr(&self, fp: &mut impl Write, precision: ExrPrecision) -> io::Result<()> {
+        write_exr_layers(fp, &[("", self)], precision)
+    }
+
+    pub fn write(&self, fp: &mut impl Write, format: ImageFormat) -> io::Result<()> {
+        match format {
+            ImageFormat::Png8 => self.write_png(fp, false),
+            ImageFormat::Png16 => self.write_png(fp, true),
+            ImageFormat::Ppm => self.write_ppm(fp),
+            ImageFormat::PpmAscii => self.write_ppm_ascii(fp),
+            ImageFormat::Exr => self.write_exr(fp, ExrPrecision::Half),
+            ImageFormat::Exr32 => self.write_exr(fp, ExrPrecision::Float),
+            ImageFormat::Hdr => self.write_hdr(fp),
+        }
+    }
+
+    /// Writes the image to `path` in `format`, or in the format matching the
+    /// extension of `path` when `format` is `None`.
+    pub fn save(&self, path: impl AsRef<Path>, format: Option<ImageFormat>) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = format
+            .or_else(|| ImageFormat::from_path(path))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("cannot tell the image format of '{}'", path.display()),
+                )
+            })?;
+
+        let mut fp = BufWriter::new(File::create(path)?);
+        self.write(&mut fp, format)?;
+        fp.flush()
+    }
+}
+
+/// Writes several framebuffers of the same size into one exr image. Each one
+/// becomes a layer whose channels are prefixed with its name, as in
+/// `diffuse.R`, and a layer with an empty name gets the plain `R`, `G`, `B`
+/// channels most viewers show by default.
+pub fn write_exr_layers(
+    fp: &mut impl Write,
+    layers: &[(&str, &Framebuffer)],
+    precision: ExrPrecision,
+) -> io::Result<()> {
+    let (width, height) = match layers.first() {
+        Some((_, fb)) => (fb.width, fb.height),
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "an exr image needs at least one layer",
+            ))
+        }
+    };
+    if layers
+        .iter()
+        .any(|(_, fb)| fb.width != width || fb.height != height)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "all exr layers must have the same size",
+        ));
+    }
+
+    let mut channels = SmallVec::new();
+    for (name, fb) in layers {
+        let pixels: Vec<Color3d> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| fb.pixel(x, y)))
+            .collect();
+        for (channel, select) in [
+            ("R", (|c: &Color3d| c.x) as fn(&Color3d) -> f64),
+            ("G", |c: &Color3d| c.y),
+            ("B", |c: &Color3d| c.z),
+        ] {
+            let samples = match precision {
+                ExrPrecision::Half => {
+                    FlatSamples::F16(pixels.iter().map(|c| f16::from_f64(select(c))).collect())
+                }
+                ExrPrecision::Float => {
+                    FlatSamples::F32(pixels.iter().map(|c| select(c) as f32).collect())
+                }
+            };
+            let channel_name = if name.is_empty() {
+                channel.to_string()
+            } else {
+                format!("{}.{}", name, channel)
+            };
+            channels.push(AnyChannel::new(channel_name.as_str(), samples));
+        }
+    }
+
+    let layer = Layer::new(
+        (width, height),
+        LayerAttributes::default(),
+        Encoding::FAST_LOSSLESS,
+        AnyChannels::sort(channels),
+    );
+
+    // the exr writer needs to seek, so encode in memory first
+    let mut buffer = Cursor::new(Vec::new());
+    Image::from_layer(layer)
+        .write()
+        .to_buffered(&mut buffer)
+        .map_err(io::Error::other)?;
+    fp.write_all(buffer.get_ref())
+}
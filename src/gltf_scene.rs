@@ -0,0 +1,331 @@
+//! glTF 2.0 scenes, as `.gltf` with their buffers or as binary `.glb`.
+//!
+//! The nodes of the default scene are walked with their transforms, every
+//! triangle primitive becomes a [`TriangleMesh`](crate::mesh::TriangleMesh)
+//! and perspective cameras become [`CameraSettings`]. Metallic-roughness
+//! materials are mapped onto the ones the renderer has:
+//!
+//! - an emissive factor brighter than black makes a [`DiffuseLight`],
+//! - a transmission factor above one half, or a blended base color that is
+//!   mostly transparent, makes a [`Dieletric`] with the index of
+//!   `KHR_materials_ior`,
+//! - a metallic factor of at least one half makes a [`Metal`] with the
+//!   roughness as fuzz,
+//! - anything else is [`Diffuse`] with the base color.
+//!
+//! Textures are not read, only the constant factors.
+
+use std::{f64::consts::PI, io, path::Path, sync::Arc};
+
+use gltf::{camera::Projection, material::AlphaMode, mesh::Mode, Gltf};
+
+use crate::{
+    aabb::Aabb,
+    environment::Gradient,
+    hittable_list::HittableList,
+    material::{Dieletric, Diffuse, DiffuseLight, Material, Metal},
+    matrix::Matrix,
+    mesh::MeshData,
+    scene_file::{CameraSettings, LoadedScene},
+    scenes::SceneSettings,
+    vec3::{Color3d, Point3d, Vec3d},
+};
+
+/// Primitives without a material get a light grey.
+const DEFAULT_ALBEDO: f64 = 0.8;
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// A perspective camera of the file.
+#[derive(Debug, Clone, Copy)]
+pub struct GltfCamera {
+    pub settings: CameraSettings,
+    /// width over height, if the camera has one
+    pub aspect_ratio: Option<f64>,
+}
+
+pub struct GltfScene {
+    /// every triangle primitive with its material
+    pub meshes: Vec<(MeshData, Arc<dyn Material>)>,
+    pub cameras: Vec<GltfCamera>,
+}
+
+fn to_material(material: &gltf::Material) -> Arc<dyn Material> {
+    if material.index().is_none() {
+        return Arc::new(Diffuse::new(Color3d::only(DEFAULT_ALBEDO)));
+    }
+
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, alpha] = pbr.base_color_factor().map(|c| c as f64);
+    let base_color = Color3d::new(r, g, b);
+    let [er, eg, eb] = material.emissive_factor().map(|c| c as f64);
+    let emissive = Color3d::new(er, eg, eb) * material.emissive_strength().unwrap_or(1.0) as f64;
+    let transmission = material
+        .transmission()
+        .map_or(0.0, |t| t.transmission_factor());
+
+    if emissive.x > 0.0 || emissive.y > 0.0 || emissive.z > 0.0 {
+        Arc::new(DiffuseLight::new(emissive))
+    } else if transmission > 0.5 || (material.alpha_mode() == AlphaMode::Blend && alpha < 0.5) {
+        Arc::new(Dieletric::new(material.ior().unwrap_or(1.5) as f64))
+    } else if pbr.metallic_factor() >= 0.5 {
+        let fuzz = (pbr.roughness_factor() as f64).clamp(0.0, 1.0);
+        Arc::new(Metal::new(base_color, fuzz))
+    } else {
+        Arc::new(Diffuse::new(base_color))
+    }
+}
+
+/// Triangles of a primitive as index triples, whatever its mode.
+fn primitive_faces(mode: Mode, indices: &[usize]) -> Vec<[usize; 3]> {
+    match mode {
+        Mode::Triangles => indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect(),
+        // every other triangle of a strip runs the other way around
+        Mode::TriangleStrip => (2..indices.len())
+            .map(|i| {
+                if i % 2 == 0 {
+                    [indices[i - 2], indices[i - 1], indices[i]]
+                } else {
+                    [indices[i - 1], indices[i - 2], indices[i]]
+                }
+            })
+            .collect(),
+        Mode::TriangleFan => (2..indices.len())
+            .map(|i| [indices[0], indices[i - 1], indices[i]])
+            .collect(),
+        // points and lines have no area
+        _ => vec![],
+    }
+}
+
+impl GltfScene {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let error = |e: gltf::Error| match e {
+            gltf::Error::Io(e) => {
+                io::Error::new(e.kind(), format!("cannot open '{}': {}", path.display(), e))
+            }
+            e => invalid(format!("{}: {}", path.display(), e)),
+        };
+        let Gltf { document, blob } = Gltf::open(path).map_err(error)?;
+        let buffers = gltf::import_buffers(&document, path.parent(), blob).map_err(error)?;
+
+        let mut scene = Self {
+            meshes: vec![],
+            cameras: vec![],
+        };
+        let mut materials: Vec<Option<Arc<dyn Material>>> = vec![None; document.materials().len()];
+        let mut default_material = None;
+
+        // the default scene, or the first one
+        let roots: Vec<gltf::Node> = match document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+        {
+            Some(s) => s.nodes().collect(),
+            None => vec![],
+        };
+        // reversed on the stack, so that nodes are visited in file order
+        let mut stack: Vec<(gltf::Node, Matrix)> = roots
+            .into_iter()
+            .rev()
+            .map(|node| (node, Matrix::identity()))
+            .collect();
+        let mut visited = 0;
+        while let Some((node, parent)) = stack.pop() {
+            // the format forbids cycles, but a broken file must not hang us
+            visited += 1;
+            if visited > document.nodes().len() {
+                return Err(invalid(format!(
+                    "{}: the node hierarchy has a cycle",
+                    path.display()
+                )));
+            }
+            let matrix = node
+                .transform()
+                .matrix()
+                .map(|column| column.map(|v| v as f64));
+            let transform = parent * Matrix::from_columns(matrix);
+
+            if let Some(camera) = node.camera() {
+                if let Projection::Perspective(perspective) = camera.projection() {
+                    let lookfrom = transform.point(Point3d::zero());
+                    let forward = transform.vector(Vec3d::new(0.0, 0.0, -1.0));
+                    let up = transform.vector(Vec3d::new(0.0, 1.0, 0.0));
+                    if !forward.near_zero() && !up.near_zero() {
+                        scene.cameras.push(GltfCamera {
+                            settings: CameraSettings {
+                                lookfrom,
+                                lookat: lookfrom + forward.unit_vector(),
+                                vup: up,
+                                vfov: perspective.yfov() as f64 * 180.0 / PI,
+                                aperture: 0.0,
+                                focus_dist: 1.0,
+                                time0: 0.0,
+                                time1: 0.0,
+                            },
+                            aspect_ratio: perspective
+                                .aspect_ratio()
+                                .map(|a| a as f64)
+                                .filter(|a| *a > 0.0),
+                        });
+                    }
+                }
+            }
+
+            if let Some(mesh) = node.mesh() {
+                for primitive in mesh.primitives() {
+                    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                    let positions: Vec<Point3d> = match reader.read_positions() {
+                        Some(positions) => positions
+                            .map(|[x, y, z]| {
+                                transform.point(Point3d::new(x as f64, y as f64, z as f64))
+                            })
+                            .collect(),
+                        None => continue,
+                    };
+                    let indices: Vec<usize> = match reader.read_indices() {
+                        Some(indices) => indices.into_u32().map(|i| i as usize).collect(),
+                        None => (0..positions.len()).collect(),
+                    };
+                    if let Some(&i) = indices.iter().find(|&&i| i >= positions.len()) {
+                        return Err(invalid(format!(
+                            "{}: mesh '{}' refers to vertex {}, there are {}",
+                            path.display(),
+                            mesh.name().unwrap_or(""),
+                            i,
+                            positions.len()
+                        )));
+                    }
+                    let mut faces = primitive_faces(primitive.mode(), &indices);
+                    if faces.is_empty() {
+                        continue;
+                    }
+                    if transform.flips_orientation() {
+                        faces.iter_mut().for_each(|f| f.swap(1, 2));
+                    }
+
+                    let normals: Vec<Vec3d> = reader
+                        .read_normals()
+                        .map(|normals| {
+                            normals
+                                .map(|[x, y, z]| {
+                                    transform.normal(Vec3d::new(x as f64, y as f64, z as f64))
+                                })
+                                .collect()
+                        })
+                        .filter(|normals: &Vec<Vec3d>| normals.len() == positions.len())
+                        .unwrap_or_default();
+                    let uvs: Vec<(f64, f64)> = reader
+                        .read_tex_coords(0)
+                        .map(|uvs| uvs.into_f32().map(|[u, v]| (u as f64, v as f64)).collect())
+                        .filter(|uvs: &Vec<(f64, f64)>| uvs.len() == positions.len())
+                        .unwrap_or_default();
+
+                    let gltf_material = primitive.material();
+                    let material = match gltf_material.index() {
+                        Some(i) => materials[i]
+                            .get_or_insert_with(|| to_material(&gltf_material))
+                            .clone(),
+                        None => default_material
+                            .get_or_insert_with(|| to_material(&gltf_material))
+                            .clone(),
+                    };
+                    scene.meshes.push((
+                        MeshData {
+                            positions,
+                            normals,
+                            uvs,
+                            faces,
+                        },
+                        material,
+                    ));
+                }
+            }
+
+            let children: Vec<gltf::Node> = node.children().collect();
+            stack.extend(children.into_iter().rev().map(|child| (child, transform)));
+        }
+
+        Ok(scene)
+    }
+
+    /// Uses `material` for every mesh instead of the ones of the file.
+    pub fn with_material(mut self, material: Arc<dyn Material>) -> Self {
+        for (_, m) in self.meshes.iter_mut() {
+            *m = material.clone();
+        }
+        self
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.meshes.iter().map(|(mesh, _)| mesh.faces.len()).sum()
+    }
+
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.meshes
+            .iter()
+            .flat_map(|(mesh, _)| mesh.positions.iter())
+            .map(|p| Aabb::new(*p, *p))
+            .reduce(|a, b| Aabb::surrounding_box(&a, &b))
+    }
+
+    /// Adds every mesh to `world`, placed by `transform` if any after the
+    /// transforms of the file.
+    pub fn add_to(self, world: &mut HittableList, transform: Option<&Matrix>) {
+        for (mut mesh, material) in self.meshes {
+            if let Some(transform) = transform {
+                mesh.transform(transform);
+            }
+            world.push(Box::new(mesh.into_mesh(material)));
+        }
+    }
+
+    /// The file as a scene of its own: seen through its first camera, or
+    /// from the front so that everything is in view if it has none, under
+    /// the sky gradient.
+    pub fn into_loaded_scene(self) -> LoadedScene {
+        let mut settings = SceneSettings::default();
+        let camera = match self.cameras.first() {
+            Some(camera) => {
+                if let Some(aspect_ratio) = camera.aspect_ratio {
+                    settings.aspect_ratio = aspect_ratio;
+                }
+                camera.settings
+            }
+            None => {
+                let bbox = self
+                    .bounding_box()
+                    .unwrap_or_else(|| Aabb::new(Point3d::only(-1.0), Point3d::only(1.0)));
+                let center = bbox.centroid();
+                let radius = ((bbox.maximum - bbox.minimum).length() / 2.0).max(1e-3);
+                let vfov: f64 = 40.0;
+                let distance = radius / (vfov.to_radians() / 2.0).sin();
+                CameraSettings {
+                    lookfrom: center + distance * Vec3d::new(0.0, 0.3, 1.0).unit_vector(),
+                    lookat: center,
+                    vup: Vec3d::new(0.0, 1.0, 0.0),
+                    vfov,
+                    aperture: 0.0,
+                    focus_dist: distance,
+                    time0: 0.0,
+                    time1: 0.0,
+                }
+            }
+        };
+
+        let mut world = HittableList::new();
+        self.add_to(&mut world, None);
+        LoadedScene {
+            settings,
+            camera,
+            world,
+            environment: Box::new(Gradient::sky()),
+        }
+    }
+}
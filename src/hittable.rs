@@ -1,6 +1,10 @@
+use std::sync::Arc;
+
 use crate::{
+    aabb::Aabb,
     material::Material,
     ray::Ray,
+    sampler::Sampler,
     vec3::{Point3d, Vec3d},
 };
 
@@ -9,8 +13,14 @@ pub struct HitRecord<'a> {
     pub p: Point3d,
     pub normal: Vec3d,
     pub t: f64,
+    /// surface coordinates for textures
+    pub u: f64,
+    pub v: f64,
+    /// how far the footprint of the ray reaches in texture coordinates, 0
+    /// for rays without a spread
+    pub uv_width: f64,
     pub font_face: bool,
-    pub material: &'a (dyn Material),
+    pub material: &'a dyn Material,
 }
 
 impl<'a> HitRecord<'a> {
@@ -27,7 +37,7 @@ impl<'a> HitRecord<'a> {
         t: f64,
         point: Point3d,
         outward_normal: Vec3d,
-        material: &'a (dyn Material),
+        material: &'a dyn Material,
         ray: &Ray,
     ) -> Self {
         let font_face = ray.direction().dot(&outward_normal) < 0.0;
@@ -40,18 +50,60 @@ impl<'a> HitRecord<'a> {
             p: point,
             normal,
             t,
+            u: 0.0,
+            v: 0.0,
+            uv_width: 0.0,
             font_face,
             material,
         }
     }
+
+    pub fn with_uv(mut self, u: f64, v: f64) -> Self {
+        self.u = u;
+        self.v = v;
+        self
+    }
+
+    /// Sets `uv_width` from the spread of `ray`, `density` being how far the
+    /// texture coordinates change per unit of length on the surface.
+    pub fn with_footprint(mut self, ray: &Ray, density: f64) -> Self {
+        if ray.spread() > 0.0 {
+            let length = ray.direction().length();
+            // a cone hitting at a grazing angle covers more of the surface
+            let cos = (self.normal.dot(&ray.direction()) / length).abs().max(0.05);
+            self.uv_width = ray.spread() * self.t * length / cos * density;
+        }
+        self
+    }
+}
+
+pub trait Hittable: Send + Sync {
+    /// The closest hit of `r` between `t_min` and `t_max`. `sampler` is for
+    /// stochastic objects such as participating media, which decide at
+    /// random where a ray scatters; deterministic shapes ignore it, and
+    /// containers pass it on to what they hold.
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut Sampler) -> Option<HitRecord<'_>>;
+
+    fn bounding_box(&self) -> Option<Aabb>;
 }
 
-pub trait Hittable {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+/// Shared objects, e.g. the geometry of instances.
+impl<H: Hittable + ?Sized> Hittable for Arc<H> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut Sampler) -> Option<HitRecord<'_>> {
+        self.as_ref().hit(r, t_min, t_max, sampler)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.as_ref().bounding_box()
+    }
 }
 
 impl Hittable for Box<dyn Hittable> {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        self.as_ref().hit(r, t_min, t_max)
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut Sampler) -> Option<HitRecord<'_>> {
+        self.as_ref().hit(r, t_min, t_max, sampler)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.as_ref().bounding_box()
     }
 }
@@ -1,11 +1,15 @@
 use crate::{
+    aabb::Aabb,
     hittable::{HitRecord, Hittable},
     material::{Dieletric, Diffuse, Metal},
+    moving_sphere::MovingSphere,
+    ray::Ray,
+    sampler::Sampler,
     sphere::Sphere,
-    utils,
     vec3::{Color3d, Point3d, Vec3d},
 };
 
+#[derive(Default)]
 pub struct HittableList {
     pub objects: Vec<Box<dyn Hittable>>,
 }
@@ -23,7 +27,17 @@ impl HittableList {
         self.objects.push(p);
     }
 
-    pub fn random_scene() -> Self {
+    pub fn random_scene(sampler: &mut Sampler) -> Self {
+        Self::random_spheres(sampler, false)
+    }
+
+    /// The random scene with the small diffuse spheres bouncing up between
+    /// time 0 and 1.
+    pub fn bouncing_scene(sampler: &mut Sampler) -> Self {
+        Self::random_spheres(sampler, true)
+    }
+
+    fn random_spheres(sampler: &mut Sampler, bouncing: bool) -> Self {
         let mut world = Self::new();
 
         let ground_material = Box::new(Diffuse::new(Color3d::only(0.5)));
@@ -35,21 +49,29 @@ impl HittableList {
 
         for a in -11..11 {
             for b in -11..11 {
-                let choose_mat = utils::random_double();
+                let choose_mat = sampler.random_double();
                 let center = Point3d::new(
-                    a as f64 + 0.9 * utils::random_double(),
+                    a as f64 + 0.9 * sampler.random_double(),
                     0.2,
-                    b as f64 + 0.9 * utils::random_double(),
+                    b as f64 + 0.9 * sampler.random_double(),
                 );
 
                 if (center - Point3d::new(4.0, 0.2, 0.0)).length() > 0.9 {
                     if choose_mat < 0.8 {
-                        let albedo = Color3d::random() * Color3d::random();
-                        let sphere = Sphere::new(center, 0.2, Box::new(Diffuse::new(albedo)));
-                        world.push(Box::new(sphere));
+                        let albedo = Color3d::random(sampler) * Color3d::random(sampler);
+                        let material = Box::new(Diffuse::new(albedo));
+                        if bouncing {
+                            let center1 =
+                                center + Vec3d::new(0.0, sampler.random_range(0.0, 0.5), 0.0);
+                            let sphere =
+                                MovingSphere::new(center, center1, 0.0, 1.0, 0.2, material);
+                            world.push(Box::new(sphere));
+                        } else {
+                            world.push(Box::new(Sphere::new(center, 0.2, material)));
+                        }
                     } else if choose_mat < 0.95 {
-                        let albedo = Color3d::random_range(0.5, 1.0);
-                        let fuzz = utils::random_range(0.0, 0.5);
+                        let albedo = Color3d::random_range(sampler, 0.5, 1.0);
+                        let fuzz = sampler.random_range(0.0, 0.5);
                         let sphere = Sphere::new(center, 0.2, Box::new(Metal::new(albedo, fuzz)));
                         world.push(Box::new(sphere));
                     } else {
@@ -81,16 +103,26 @@ impl HittableList {
 }
 
 impl Hittable for HittableList {
-    fn hit(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut Sampler) -> Option<HitRecord<'_>> {
         let mut temp_rec: Option<HitRecord> = None;
         let mut closest_so_far = t_max;
 
         for object in self.objects.iter() {
-            if let Some(result) = object.hit(&r, t_min, closest_so_far) {
+            if let Some(result) = object.hit(r, t_min, closest_so_far, sampler) {
                 closest_so_far = result.t;
                 temp_rec = Some(result);
             }
         }
         temp_rec
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut objects = self.objects.iter();
+        let first = objects.next()?.bounding_box()?;
+        objects.try_fold(first, |acc, object| {
+            object
+                .bounding_box()
+                .map(|bbox| Aabb::surrounding_box(&acc, &bbox))
+        })
+    }
 }
@@ -0,0 +1,136 @@
+//! Images read into linear colors, for environment maps and textures.
+
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+};
+
+use jpeg_decoder::PixelFormat;
+
+use crate::{color::srgb_to_linear, rgbe::read_hdr, vec3::Color3d};
+
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// row by row from the top
+    pub pixels: Vec<Color3d>,
+}
+
+impl Image {
+    /// Loads a radiance `.hdr`, an OpenEXR `.exr`, a jpeg or a png image.
+    /// The 8 bit srgb values of jpeg and png are decoded to linear.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("hdr") => {
+                let (width, height, pixels) = read_hdr(&mut BufReader::new(File::open(path)?))?;
+                Ok(Self {
+                    width,
+                    height,
+                    pixels,
+                })
+            }
+            Some("exr") => Self::load_exr(path),
+            Some("jpg" | "jpeg") => Self::load_jpeg(path),
+            _ => Self::load_png(path),
+        }
+    }
+
+    fn load_exr(path: &Path) -> io::Result<Self> {
+        let image = exr::prelude::read_first_rgba_layer_from_file(
+            path,
+            |resolution, _| {
+                (
+                    resolution.width(),
+                    vec![Color3d::zero(); resolution.width() * resolution.height()],
+                )
+            },
+            |(width, pixels), position, (r, g, b, _): (f32, f32, f32, f32)| {
+                pixels[position.y() * *width + position.x()] =
+                    Color3d::new(r as f64, g as f64, b as f64)
+            },
+        )
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let (width, pixels) = image.layer_data.channel_data.pixels;
+        if width == 0 || pixels.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "exr image is empty",
+            ));
+        }
+        let height = pixels.len() / width;
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn load_jpeg(path: &Path) -> io::Result<Self> {
+        let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut decoder = jpeg_decoder::Decoder::new(BufReader::new(File::open(path)?));
+        let data = decoder.decode().map_err(invalid)?;
+        let info = decoder
+            .info()
+            .ok_or_else(|| invalid(jpeg_decoder::Error::Format("no image".into())))?;
+
+        let c = |v: u8| srgb_to_linear(v as f64 / 255.0);
+        let pixels = match info.pixel_format {
+            PixelFormat::L8 => data.iter().map(|&v| Color3d::only(c(v))).collect(),
+            PixelFormat::L16 => data
+                .chunks_exact(2)
+                .map(|v| {
+                    let v = u16::from_ne_bytes([v[0], v[1]]) as f64 / 65535.0;
+                    Color3d::only(srgb_to_linear(v))
+                })
+                .collect(),
+            PixelFormat::RGB24 => data
+                .chunks_exact(3)
+                .map(|p| Color3d::new(c(p[0]), c(p[1]), c(p[2])))
+                .collect(),
+            PixelFormat::CMYK32 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "cmyk jpeg images are not supported",
+                ))
+            }
+        };
+        Ok(Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    fn load_png(path: &Path) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data)?;
+
+        let channels = info.color_type.samples();
+        let pixels = data[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|p| {
+                let c = |v: u8| srgb_to_linear(v as f64 / 255.0);
+                match channels {
+                    1 | 2 => Color3d::only(c(p[0])),
+                    _ => Color3d::new(c(p[0]), c(p[1]), c(p[2])),
+                }
+            })
+            .collect();
+        Ok(Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+}
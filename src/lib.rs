@@ -0,0 +1,49 @@
+//! A small ray tracer following "Ray Tracing in One Weekend".
+//!
+//! Build a [`hittable_list::HittableList`] out of spheres and materials,
+//! optionally wrap it in a bvh, point a [`camera::Camera`] at it and hand both
+//! to a [`renderer::Renderer`] as a [`scenes::Scene`], together with the
+//! [`environment::Environment`] that rays leaving the scene see.
+
+pub mod aabb;
+pub mod bvh;
+pub mod camera;
+pub mod color;
+pub mod constant_medium;
+pub mod cuboid;
+pub mod distribution;
+pub mod environment;
+pub mod framebuffer;
+pub mod gltf_scene;
+pub mod hittable;
+pub mod hittable_list;
+pub mod image;
+pub mod linear_bvh;
+pub mod material;
+pub mod matrix;
+pub mod mesh;
+pub mod moving_sphere;
+pub mod noise;
+pub mod obj;
+pub mod plane;
+pub mod ply;
+pub mod quad;
+pub mod ray;
+pub mod renderer;
+pub mod rgbe;
+pub mod sampler;
+pub mod scene_file;
+pub mod scenes;
+pub mod sky;
+pub mod sphere;
+pub mod stl;
+pub mod texture;
+pub mod tonemap;
+pub mod transformed;
+pub mod triangle;
+pub mod utils;
+pub mod vec3;
+
+pub use framebuffer::{Framebuffer, ImageFormat};
+pub use renderer::Renderer;
+pub use tonemap::{ToneMapper, ToneMapping};
@@ -0,0 +1,411 @@
+use std::fmt::Display;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    hittable_list::HittableList,
+    ray::Ray,
+    sampler::Sampler,
+    vec3::Point3d,
+};
+
+const BIN_COUNT: usize = 12;
+const MAX_PRIMITIVES_IN_LEAF: usize = 4;
+// cost of visiting an interior node relative to intersecting one primitive
+const TRAVERSAL_COST: f64 = 0.125;
+// past this depth nodes are split at the median, which bounds the tree
+// depth (and so the traversal stack) for any input
+const SAH_DEPTH_LIMIT: usize = 32;
+const STACK_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy)]
+struct LinearNode {
+    bbox: Aabb,
+    // leaf: index of the first primitive, interior: index of the second child
+    offset: usize,
+    // zero for interior nodes
+    count: usize,
+    axis: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PrimitiveInfo {
+    index: usize,
+    bbox: Aabb,
+    centroid: Point3d,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BvhStats {
+    pub primitive_count: usize,
+    pub node_count: usize,
+    pub leaf_count: usize,
+    pub max_depth: usize,
+    pub min_leaf_size: usize,
+    pub max_leaf_size: usize,
+}
+
+impl BvhStats {
+    pub fn average_leaf_size(&self) -> f64 {
+        if self.leaf_count == 0 {
+            0.0
+        } else {
+            self.primitive_count as f64 / self.leaf_count as f64
+        }
+    }
+
+    fn record_leaf(&mut self, size: usize, depth: usize) {
+        self.min_leaf_size = if self.leaf_count == 0 {
+            size
+        } else {
+            self.min_leaf_size.min(size)
+        };
+        self.max_leaf_size = self.max_leaf_size.max(size);
+        self.max_depth = self.max_depth.max(depth);
+        self.leaf_count += 1;
+    }
+}
+
+impl Display for BvhStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} primitives, {} nodes, {} leaves, depth {}, leaf size {}..{} (avg {:.2})",
+            self.primitive_count,
+            self.node_count,
+            self.leaf_count,
+            self.max_depth,
+            self.min_leaf_size,
+            self.max_leaf_size,
+            self.average_leaf_size(),
+        )
+    }
+}
+
+/// The flat node array behind [`LinearBvh`], over primitives that are only
+/// known by their bounding boxes. Shapes made of many small parts, like
+/// triangle meshes, use it directly to avoid boxing every part.
+#[derive(Debug, Clone)]
+pub struct BvhTree {
+    nodes: Vec<LinearNode>,
+    stats: BvhStats,
+}
+
+/// Bvh built with the binned surface area heuristic and stored as a flat,
+/// depth-first array of nodes. The first child of an interior node directly
+/// follows it, so traversal only needs a small stack of node indices.
+/// Objects without a bounding box, like infinite planes, are kept aside and
+/// tested on every ray.
+pub struct LinearBvh {
+    primitives: Vec<Box<dyn Hittable>>,
+    unbounded: Vec<Box<dyn Hittable>>,
+    tree: BvhTree,
+}
+
+fn union(a: Option<Aabb>, b: &Aabb) -> Aabb {
+    match a {
+        Some(a) => Aabb::surrounding_box(&a, b),
+        None => *b,
+    }
+}
+
+impl LinearBvh {
+    pub fn new(list: HittableList) -> Self {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = list
+            .objects
+            .into_iter()
+            .partition(|object| object.bounding_box().is_some());
+
+        let boxes: Vec<Aabb> = bounded
+            .iter()
+            .map(|object| object.bounding_box().unwrap())
+            .collect();
+        let (tree, order) = BvhTree::new(&boxes);
+
+        // reorder the primitives so that every leaf covers a contiguous range
+        let mut objects: Vec<Option<Box<dyn Hittable>>> = bounded.into_iter().map(Some).collect();
+        let primitives = order
+            .iter()
+            .map(|&index| objects[index].take().unwrap())
+            .collect();
+
+        Self {
+            primitives,
+            unbounded,
+            tree,
+        }
+    }
+
+    pub fn stats(&self) -> &BvhStats {
+        self.tree.stats()
+    }
+}
+
+impl BvhTree {
+    /// Builds the tree over `boxes` and returns it with the order to store
+    /// the primitives in: leaves refer to contiguous ranges of that order.
+    pub fn new(boxes: &[Aabb]) -> (Self, Vec<usize>) {
+        let mut info: Vec<PrimitiveInfo> = boxes
+            .iter()
+            .enumerate()
+            .map(|(index, bbox)| PrimitiveInfo {
+                index,
+                bbox: *bbox,
+                centroid: bbox.centroid(),
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(2 * info.len());
+        let mut stats = BvhStats {
+            primitive_count: info.len(),
+            ..BvhStats::default()
+        };
+        if !info.is_empty() {
+            Self::build(&mut info, 0, &mut nodes, &mut stats, 0);
+        }
+        stats.node_count = nodes.len();
+
+        let order = info.iter().map(|p| p.index).collect();
+        (Self { nodes, stats }, order)
+    }
+
+    pub fn stats(&self) -> &BvhStats {
+        &self.stats
+    }
+
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bbox)
+    }
+
+    /// Walks the nodes `r` passes through, nearest first, and calls
+    /// `hit_primitive` with the position (in the order returned by
+    /// [`BvhTree::new`]) of every primitive found in them and the closest
+    /// distance so far. Returns the closest of the hits it reported.
+    pub fn hit<'a>(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut hit_primitive: impl FnMut(usize, f64) -> Option<HitRecord<'a>>,
+    ) -> Option<HitRecord<'a>> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let direction = r.direction();
+        let mut closest_so_far = t_max;
+        let mut temp_rec = None;
+
+        let mut stack = [0usize; STACK_SIZE];
+        let mut stack_len = 0;
+        let mut current = 0;
+
+        loop {
+            let node = &self.nodes[current];
+            if node.bbox.hit(r, t_min, closest_so_far) {
+                if node.count > 0 {
+                    for position in node.offset..node.offset + node.count {
+                        if let Some(result) = hit_primitive(position, closest_so_far) {
+                            closest_so_far = result.t;
+                            temp_rec = Some(result);
+                        }
+                    }
+                } else {
+                    // visit the near child first so that the far one is
+                    // more likely to be culled by `closest_so_far`
+                    let (near, far) = if direction[node.axis] < 0.0 {
+                        (node.offset, current + 1)
+                    } else {
+                        (current + 1, node.offset)
+                    };
+                    stack[stack_len] = far;
+                    stack_len += 1;
+                    current = near;
+                    continue;
+                }
+            }
+
+            if stack_len == 0 {
+                break;
+            }
+            stack_len -= 1;
+            current = stack[stack_len];
+        }
+
+        temp_rec
+    }
+
+    fn build(
+        info: &mut [PrimitiveInfo],
+        first: usize,
+        nodes: &mut Vec<LinearNode>,
+        stats: &mut BvhStats,
+        depth: usize,
+    ) -> usize {
+        let bbox = info
+            .iter()
+            .fold(None, |acc, p| Some(union(acc, &p.bbox)))
+            .unwrap();
+        let centroid_bounds = info
+            .iter()
+            .fold(None, |acc, p| {
+                Some(union(acc, &Aabb::new(p.centroid, p.centroid)))
+            })
+            .unwrap();
+        let axis = centroid_bounds.longest_axis();
+
+        let node_index = nodes.len();
+        nodes.push(LinearNode {
+            bbox,
+            offset: first,
+            count: info.len(),
+            axis,
+        });
+
+        let lo = centroid_bounds.minimum[axis];
+        let hi = centroid_bounds.maximum[axis];
+        if info.len() == 1 || hi - lo <= f64::EPSILON {
+            stats.record_leaf(info.len(), depth);
+            return node_index;
+        }
+
+        let split = if depth < SAH_DEPTH_LIMIT {
+            Self::sah_split(info, &bbox, axis, lo, hi)
+        } else {
+            Some(Self::median_split(info, axis))
+        };
+        let mid = match split {
+            Some(mid) => mid,
+            None => {
+                stats.record_leaf(info.len(), depth);
+                return node_index;
+            }
+        };
+
+        let (left, right) = info.split_at_mut(mid);
+        Self::build(left, first, nodes, stats, depth + 1);
+        let second = Self::build(right, first + mid, nodes, stats, depth + 1);
+
+        nodes[node_index].offset = second;
+        nodes[node_index].count = 0;
+        node_index
+    }
+
+    /// Partitions `info` at the cheapest bin boundary and returns the split
+    /// position, or `None` if a leaf is cheaper than any split.
+    fn sah_split(
+        info: &mut [PrimitiveInfo],
+        bbox: &Aabb,
+        axis: usize,
+        lo: f64,
+        hi: f64,
+    ) -> Option<usize> {
+        let bin_of = |p: &PrimitiveInfo| {
+            let b = (BIN_COUNT as f64 * (p.centroid[axis] - lo) / (hi - lo)) as usize;
+            b.min(BIN_COUNT - 1)
+        };
+
+        let mut counts = [0usize; BIN_COUNT];
+        let mut bounds: [Option<Aabb>; BIN_COUNT] = [None; BIN_COUNT];
+        for p in info.iter() {
+            let b = bin_of(p);
+            counts[b] += 1;
+            bounds[b] = Some(union(bounds[b], &p.bbox));
+        }
+
+        // sweep from the right to get the cost of every right-hand side,
+        // then from the left to combine it with the left-hand side
+        let mut right_area = [0.0; BIN_COUNT];
+        let mut right_count = [0usize; BIN_COUNT];
+        let mut acc_box = None;
+        let mut acc_count = 0;
+        for b in (1..BIN_COUNT).rev() {
+            if let Some(bin_box) = &bounds[b] {
+                acc_box = Some(union(acc_box, bin_box));
+            }
+            acc_count += counts[b];
+            right_area[b] = acc_box.map_or(0.0, |a| a.surface_area());
+            right_count[b] = acc_count;
+        }
+
+        let mut best_cost = f64::INFINITY;
+        let mut best_bin = 0;
+        let mut acc_box = None;
+        let mut acc_count = 0;
+        for b in 0..BIN_COUNT - 1 {
+            if let Some(bin_box) = &bounds[b] {
+                acc_box = Some(union(acc_box, bin_box));
+            }
+            acc_count += counts[b];
+            if acc_count == 0 || right_count[b + 1] == 0 {
+                continue;
+            }
+            let left_area = acc_box.map_or(0.0, |a| a.surface_area());
+            let cost = acc_count as f64 * left_area + right_count[b + 1] as f64 * right_area[b + 1];
+            if cost < best_cost {
+                best_cost = cost;
+                best_bin = b;
+            }
+        }
+
+        let total_area = bbox.surface_area();
+        let split_cost = if total_area > 0.0 {
+            TRAVERSAL_COST + best_cost / total_area
+        } else {
+            TRAVERSAL_COST + info.len() as f64
+        };
+        if info.len() <= MAX_PRIMITIVES_IN_LEAF && split_cost >= info.len() as f64 {
+            return None;
+        }
+
+        // in-place partition by bin
+        let mut mid = 0;
+        for i in 0..info.len() {
+            if bin_of(&info[i]) <= best_bin {
+                info.swap(i, mid);
+                mid += 1;
+            }
+        }
+
+        if mid == 0 || mid == info.len() {
+            mid = Self::median_split(info, axis);
+        }
+        Some(mid)
+    }
+
+    fn median_split(info: &mut [PrimitiveInfo], axis: usize) -> usize {
+        let mid = info.len() / 2;
+        info.select_nth_unstable_by(mid, |a, b| {
+            a.centroid[axis]
+                .partial_cmp(&b.centroid[axis])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        mid
+    }
+}
+
+impl Hittable for LinearBvh {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut Sampler) -> Option<HitRecord<'_>> {
+        let mut closest_so_far = t_max;
+        let mut temp_rec = None;
+        for object in self.unbounded.iter() {
+            if let Some(result) = object.hit(r, t_min, closest_so_far, sampler) {
+                closest_so_far = result.t;
+                temp_rec = Some(result);
+            }
+        }
+
+        self.tree
+            .hit(r, t_min, closest_so_far, |position, closest| {
+                self.primitives[position].hit(r, t_min, closest, sampler)
+            })
+            .or(temp_rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+        self.tree.bounding_box()
+    }
+}
@@ -1,80 +1,229 @@
-use std::ops::Neg;
+use std::{f64::consts::PI, ops::Neg, sync::Arc};
 
 use num_traits::pow;
 
 use crate::{
     hittable::HitRecord,
     ray::Ray,
-    utils,
+    sampler::Sampler,
+    texture::{SolidTexture, Texture},
     vec3::{Color3d, Vec3d},
 };
 
-pub trait Material {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color3d, Ray)>;
+pub trait Material: Send + Sync {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, sampler: &mut Sampler)
+        -> Option<(Color3d, Ray)>;
+
+    /// Radiance given off at the hit point, black for anything that is not a
+    /// light.
+    fn emitted(&self, _r_in: &Ray, _rec: &HitRecord) -> Color3d {
+        Color3d::zero()
+    }
+
+    /// Light scattered towards `direction` and the density `scatter` picks
+    /// that direction with, so that the first divided by the second is the
+    /// attenuation `scatter` reports. Only materials that do not scatter
+    /// into single directions, like a mirror does, can answer this, and the
+    /// renderer samples the environment directly for those.
+    fn scattering(
+        &self,
+        _r_in: &Ray,
+        _rec: &HitRecord,
+        _direction: &Vec3d,
+    ) -> Option<(Color3d, f64)> {
+        None
+    }
 }
 
 impl Material for Box<dyn Material> {
-    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Color3d, Ray)> {
-        self.as_ref().scatter(ray_in, hit_record)
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        sampler: &mut Sampler,
+    ) -> Option<(Color3d, Ray)> {
+        self.as_ref().scatter(ray_in, hit_record, sampler)
+    }
+
+    fn emitted(&self, ray_in: &Ray, hit_record: &HitRecord) -> Color3d {
+        self.as_ref().emitted(ray_in, hit_record)
+    }
+
+    fn scattering(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        direction: &Vec3d,
+    ) -> Option<(Color3d, f64)> {
+        self.as_ref().scattering(ray_in, hit_record, direction)
+    }
+}
+
+impl Material for Arc<dyn Material> {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        sampler: &mut Sampler,
+    ) -> Option<(Color3d, Ray)> {
+        self.as_ref().scatter(ray_in, hit_record, sampler)
+    }
+
+    fn emitted(&self, ray_in: &Ray, hit_record: &HitRecord) -> Color3d {
+        self.as_ref().emitted(ray_in, hit_record)
+    }
+
+    fn scattering(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        direction: &Vec3d,
+    ) -> Option<(Color3d, f64)> {
+        self.as_ref().scattering(ray_in, hit_record, direction)
     }
 }
 
 impl<M: Material> Material for Box<M> {
-    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Color3d, Ray)> {
-        self.as_ref().scatter(ray_in, hit_record)
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        sampler: &mut Sampler,
+    ) -> Option<(Color3d, Ray)> {
+        self.as_ref().scatter(ray_in, hit_record, sampler)
+    }
+
+    fn emitted(&self, ray_in: &Ray, hit_record: &HitRecord) -> Color3d {
+        self.as_ref().emitted(ray_in, hit_record)
+    }
+
+    fn scattering(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        direction: &Vec3d,
+    ) -> Option<(Color3d, f64)> {
+        self.as_ref().scattering(ray_in, hit_record, direction)
     }
 }
 
 #[derive(Clone)]
 
-pub struct Diffuse {
-    albedo: Color3d,
+pub struct Diffuse<T: Texture = SolidTexture> {
+    albedo: T,
 }
 
 impl Diffuse {
     pub fn new(a: Color3d) -> Self {
-        Self { albedo: a }
+        Self::textured(SolidTexture::new(a))
+    }
+}
+
+impl<T: Texture> Diffuse<T> {
+    pub fn textured(albedo: T) -> Self {
+        Self { albedo }
     }
 }
 
-impl Material for Diffuse {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color3d, Ray)> {
-        let mut scatter_direction = rec.normal + Vec3d::random_unit_vector();
+impl<T: Texture> Material for Diffuse<T> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut Sampler,
+    ) -> Option<(Color3d, Ray)> {
+        let mut scatter_direction = rec.normal + Vec3d::random_unit_vector(sampler);
         if scatter_direction.near_zero() {
             scatter_direction = rec.normal;
         }
-        Some((self.albedo, Ray::new(rec.p, scatter_direction)))
+        Some((
+            self.albedo.sample(rec),
+            Ray::new(rec.p, scatter_direction, r_in.time()),
+        ))
+    }
+
+    fn scattering(
+        &self,
+        _r_in: &Ray,
+        rec: &HitRecord,
+        direction: &Vec3d,
+    ) -> Option<(Color3d, f64)> {
+        // normal plus a random unit vector is cosine distributed
+        let pdf = rec.normal.dot(&direction.unit_vector()).max(0.0) / PI;
+        Some((self.albedo.sample(rec) * pdf, pdf))
     }
 }
 
 #[derive(Clone, Copy)]
-pub struct Metal {
-    albedo: Color3d,
+pub struct Metal<T: Texture = SolidTexture> {
+    albedo: T,
     fuzz: f64,
 }
 
 impl Metal {
     pub fn new(a: Color3d, f: f64) -> Self {
+        Self::textured(SolidTexture::new(a), f)
+    }
+}
+
+impl<T: Texture> Metal<T> {
+    pub fn textured(albedo: T, f: f64) -> Self {
         Self {
-            albedo: a,
+            albedo,
             fuzz: if f < 1.0 { f } else { 1.0 },
         }
     }
 }
 
-impl Material for Metal {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color3d, Ray)> {
+impl<T: Texture> Material for Metal<T> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut Sampler,
+    ) -> Option<(Color3d, Ray)> {
         let reflected = r_in.direction().unit_vector().reflect(&rec.normal);
         let scattered = Ray::new(
             rec.p,
-            reflected + self.fuzz * Vec3d::random_in_unit_sphere(),
+            reflected + self.fuzz * Vec3d::random_in_unit_sphere(sampler),
+            r_in.time(),
         );
         if scattered.direction().dot(&rec.normal) > 0.0 {
-            Some((self.albedo, scattered))
+            Some((self.albedo.sample(rec), scattered))
         } else {
             None
         }
     }
+
+    fn scattering(&self, r_in: &Ray, rec: &HitRecord, direction: &Vec3d) -> Option<(Color3d, f64)> {
+        if self.fuzz <= 0.0 {
+            return None;
+        }
+
+        // scatter picks a point uniformly in the ball of radius fuzz around
+        // the unit reflection, so the density of a direction is the part of
+        // the ball's volume along it
+        let d = direction.unit_vector();
+        let reflected = r_in.direction().unit_vector().reflect(&rec.normal);
+        let b = d.dot(&reflected);
+        let disc = b * b - (1.0 - self.fuzz * self.fuzz);
+        if disc <= 0.0 {
+            return Some((Color3d::zero(), 0.0));
+        }
+        let t_near = (b - disc.sqrt()).max(0.0);
+        let t_far = b + disc.sqrt();
+        if t_far <= 0.0 {
+            return Some((Color3d::zero(), 0.0));
+        }
+        let pdf = (pow(t_far, 3) - pow(t_near, 3)) / (4.0 * PI * pow(self.fuzz, 3));
+
+        // anything scattered below the surface is absorbed
+        if d.dot(&rec.normal) > 0.0 {
+            Some((self.albedo.sample(rec) * pdf, pdf))
+        } else {
+            Some((Color3d::zero(), pdf))
+        }
+    }
 }
 #[derive(Clone)]
 pub struct Dieletric {
@@ -97,7 +246,12 @@ impl Dieletric {
 }
 
 impl Material for Dieletric {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color3d, Ray)> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut Sampler,
+    ) -> Option<(Color3d, Ray)> {
         let attenuation = Color3d::only(1.0);
         let refraction_ratio = if rec.font_face {
             1.0 / self.ir
@@ -113,13 +267,88 @@ impl Material for Dieletric {
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
 
         let direction = if cannot_refract
-            || Dieletric::reflectance(cos_theta, refraction_ratio) > utils::random_double()
+            || Dieletric::reflectance(cos_theta, refraction_ratio) > sampler.random_double()
         {
             unit_direction.reflect(&rec.normal)
         } else {
             unit_direction.refract(&rec.normal, refraction_ratio)
         };
 
-        Some((attenuation, Ray::new(rec.p, direction)))
+        Some((attenuation, Ray::new(rec.p, direction, r_in.time())))
+    }
+}
+
+/// The phase function of a medium that scatters light equally in every
+/// direction, see [`ConstantMedium`](crate::constant_medium::ConstantMedium).
+#[derive(Clone, Copy)]
+pub struct Isotropic<T: Texture = SolidTexture> {
+    albedo: T,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color3d) -> Self {
+        Self::textured(SolidTexture::new(albedo))
+    }
+}
+
+impl<T: Texture> Isotropic<T> {
+    pub fn textured(albedo: T) -> Self {
+        Self { albedo }
+    }
+}
+
+impl<T: Texture> Material for Isotropic<T> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        sampler: &mut Sampler,
+    ) -> Option<(Color3d, Ray)> {
+        let scattered = Ray::new(rec.p, Vec3d::random_unit_vector(sampler), r_in.time());
+        Some((self.albedo.sample(rec), scattered))
+    }
+
+    fn scattering(
+        &self,
+        _r_in: &Ray,
+        rec: &HitRecord,
+        _direction: &Vec3d,
+    ) -> Option<(Color3d, f64)> {
+        let pdf = 1.0 / (4.0 * PI);
+        Some((self.albedo.sample(rec) * pdf, pdf))
+    }
+}
+
+/// Area light, emits `emit` from every point of the surface and absorbs
+/// anything that hits it.
+#[derive(Clone, Copy)]
+pub struct DiffuseLight<T: Texture = SolidTexture> {
+    emit: T,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color3d) -> Self {
+        Self::textured(SolidTexture::new(emit))
+    }
+}
+
+impl<T: Texture> DiffuseLight<T> {
+    pub fn textured(emit: T) -> Self {
+        Self { emit }
+    }
+}
+
+impl<T: Texture> Material for DiffuseLight<T> {
+    fn scatter(
+        &self,
+        _r_in: &Ray,
+        _rec: &HitRecord,
+        _sampler: &mut Sampler,
+    ) -> Option<(Color3d, Ray)> {
+        None
+    }
+
+    fn emitted(&self, _r_in: &Ray, rec: &HitRecord) -> Color3d {
+        self.emit.sample(rec)
     }
 }
@@ -0,0 +1,197 @@
+use std::ops::Mul;
+
+use crate::{
+    aabb::Aabb,
+    utils::degrees_to_radians,
+    vec3::{Point3d, Vec3d},
+};
+
+/// An affine transform as the top three rows of a 4x4 matrix, the last row
+/// is always `0 0 0 1`. Points are columns, so `a * b` applies `b` first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix {
+    pub rows: [[f64; 4]; 3],
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Matrix {
+    pub fn new(rows: [[f64; 4]; 3]) -> Self {
+        Self { rows }
+    }
+
+    pub fn identity() -> Self {
+        Self::scaling(Vec3d::only(1.0))
+    }
+
+    /// From the columns of a 4x4 matrix, as glTF stores them. The last row
+    /// is dropped.
+    pub fn from_columns(columns: [[f64; 4]; 4]) -> Self {
+        let row = |i: usize| columns.map(|column| column[i]);
+        Self::new([row(0), row(1), row(2)])
+    }
+
+    pub fn translation(offset: Vec3d) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+        ])
+    }
+
+    pub fn scaling(scale: Vec3d) -> Self {
+        Self::new([
+            [scale.x, 0.0, 0.0, 0.0],
+            [0.0, scale.y, 0.0, 0.0],
+            [0.0, 0.0, scale.z, 0.0],
+        ])
+    }
+
+    /// Turns counterclockwise by `degrees` about `axis`, seen from its tip.
+    pub fn rotation(axis: Vec3d, degrees: f64) -> Self {
+        let Vec3d { x, y, z } = axis.unit_vector();
+        let (sin, cos) = degrees_to_radians(degrees).sin_cos();
+        let k = 1.0 - cos;
+        Self::new([
+            [
+                k * x * x + cos,
+                k * x * y - sin * z,
+                k * x * z + sin * y,
+                0.0,
+            ],
+            [
+                k * x * y + sin * z,
+                k * y * y + cos,
+                k * y * z - sin * x,
+                0.0,
+            ],
+            [
+                k * x * z - sin * y,
+                k * y * z + sin * x,
+                k * z * z + cos,
+                0.0,
+            ],
+        ])
+    }
+
+    pub fn rotation_x(degrees: f64) -> Self {
+        Self::rotation(Vec3d::new(1.0, 0.0, 0.0), degrees)
+    }
+
+    pub fn rotation_y(degrees: f64) -> Self {
+        Self::rotation(Vec3d::new(0.0, 1.0, 0.0), degrees)
+    }
+
+    pub fn rotation_z(degrees: f64) -> Self {
+        Self::rotation(Vec3d::new(0.0, 0.0, 1.0), degrees)
+    }
+
+    /// Scales, turns about the x, then the y, then the z axis by `rotation`
+    /// degrees and moves by `translation`, the way scene files place
+    /// objects.
+    pub fn placement(scale: Vec3d, rotation: Vec3d, translation: Vec3d) -> Self {
+        Self::translation(translation)
+            * Self::rotation_z(rotation.z)
+            * Self::rotation_y(rotation.y)
+            * Self::rotation_x(rotation.x)
+            * Self::scaling(scale)
+    }
+
+    fn column(&self, j: usize) -> Vec3d {
+        Vec3d::new(self.rows[0][j], self.rows[1][j], self.rows[2][j])
+    }
+
+    pub fn point(&self, p: Point3d) -> Point3d {
+        self.vector(p) + self.column(3)
+    }
+
+    /// Directions are not moved by the translation.
+    pub fn vector(&self, v: Vec3d) -> Vec3d {
+        let row = |r: [f64; 4]| r[0] * v.x + r[1] * v.y + r[2] * v.z;
+        Vec3d::new(row(self.rows[0]), row(self.rows[1]), row(self.rows[2]))
+    }
+
+    /// Normals go through the inverse transpose to stay perpendicular to
+    /// the surface. The result is not normalized.
+    pub fn normal(&self, n: Vec3d) -> Vec3d {
+        let [a, b, c] = [self.column(0), self.column(1), self.column(2)];
+        let cofactors = n.x * b.cross(&c) + n.y * c.cross(&a) + n.z * a.cross(&b);
+        if self.determinant() < 0.0 {
+            -cofactors
+        } else {
+            cofactors
+        }
+    }
+
+    pub fn determinant(&self) -> f64 {
+        let [a, b, c] = [self.column(0), self.column(1), self.column(2)];
+        a.dot(&b.cross(&c))
+    }
+
+    /// Whether the transform mirrors, which turns faces inside out.
+    pub fn flips_orientation(&self) -> bool {
+        self.determinant() < 0.0
+    }
+
+    /// `None` if the transform flattens space.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let [a, b, c] = [self.column(0), self.column(1), self.column(2)];
+        // the rows of the inverse are the cross products of the columns
+        let rows = [b.cross(&c) / det, c.cross(&a) / det, a.cross(&b) / det];
+        let t = self.column(3);
+        let row = |r: Vec3d| [r.x, r.y, r.z, -r.dot(&t)];
+        Some(Self::new([row(rows[0]), row(rows[1]), row(rows[2])]))
+    }
+
+    /// Blends into `other` as `s` goes from 0 to 1, entry by entry. Moves
+    /// and scaling blend exactly, turns only approximately: the larger the
+    /// angle between both ends the more objects shrink half way.
+    pub fn lerp(&self, other: &Matrix, s: f64) -> Self {
+        let mut rows = self.rows;
+        for (row, other) in rows.iter_mut().zip(other.rows) {
+            for (value, other) in row.iter_mut().zip(other) {
+                *value += s * (other - *value);
+            }
+        }
+        Self::new(rows)
+    }
+
+    /// The smallest box around the transformed `bbox`.
+    pub fn bounding_box(&self, bbox: &Aabb) -> Aabb {
+        let mut minimum = self.column(3);
+        let mut maximum = minimum;
+        for i in 0..3 {
+            for j in 0..3 {
+                let a = self.rows[i][j] * bbox.minimum[j];
+                let b = self.rows[i][j] * bbox.maximum[j];
+                minimum[i] += a.min(b);
+                maximum[i] += a.max(b);
+            }
+        }
+        Aabb::new(minimum, maximum)
+    }
+}
+
+impl Mul for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: Matrix) -> Matrix {
+        // the axes of `rhs` are turned, its origin is moved as well
+        let columns = [
+            self.vector(rhs.column(0)),
+            self.vector(rhs.column(1)),
+            self.vector(rhs.column(2)),
+            self.point(rhs.column(3)),
+        ];
+        let row = |i: usize| columns.map(|column| column[i]);
+        Matrix::new([row(0), row(1), row(2)])
+    }
+}
@@ -0,0 +1,328 @@
+//! Indexed triangle meshes: faces refer to shared vertices, and the mesh
+//! keeps its own bvh so that it enters a scene as a single object.
+
+use std::borrow::Borrow;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    linear_bvh::{BvhStats, BvhTree},
+    material::Material,
+    matrix::Matrix,
+    ray::Ray,
+    sampler::Sampler,
+    triangle::{barycentric_density, intersect_triangle, triangle_bounding_box},
+    vec3::{Point3d, Vec3d},
+};
+
+/// Normals for smooth shading: every vertex gets the average of the normals
+/// of the faces around it, weighted by their area.
+pub fn vertex_normals(positions: &[Point3d], faces: &[[usize; 3]]) -> Vec<Vec3d> {
+    let mut normals = vec![Vec3d::zero(); positions.len()];
+    for &[a, b, c] in faces {
+        let p = positions;
+        // the length of the cross product is twice the area
+        let n = (p[b] - p[a]).cross(&(p[c] - p[a]));
+        for i in [a, b, c] {
+            normals[i] += n;
+        }
+    }
+    normals
+}
+
+/// Vertices and faces as loaders read them, before they are turned into a
+/// [`TriangleMesh`].
+#[derive(Debug, Clone, Default)]
+pub struct MeshData {
+    pub positions: Vec<Point3d>,
+    /// one per vertex, or empty
+    pub normals: Vec<Vec3d>,
+    /// one per vertex, or empty
+    pub uvs: Vec<(f64, f64)>,
+    pub faces: Vec<[usize; 3]>,
+}
+
+impl MeshData {
+    pub fn transform(&mut self, transform: &Matrix) {
+        for p in self.positions.iter_mut() {
+            *p = transform.point(*p);
+        }
+        for n in self.normals.iter_mut() {
+            *n = transform.normal(*n);
+        }
+        if transform.flips_orientation() {
+            for face in self.faces.iter_mut() {
+                face.swap(1, 2);
+            }
+        }
+    }
+
+    /// Replaces the normals by [`vertex_normals`].
+    pub fn smooth_normals(&mut self) {
+        self.normals = vertex_normals(&self.positions, &self.faces);
+    }
+
+    /// A mesh of one material, smooth shaded if there are normals.
+    pub fn into_mesh<M: Material>(self, material: M) -> TriangleMesh<M> {
+        let mut mesh = TriangleMesh::new(self.positions, self.faces, material);
+        if !self.normals.is_empty() {
+            mesh = mesh.with_normals(self.normals);
+        }
+        if !self.uvs.is_empty() {
+            mesh = mesh.with_uvs(self.uvs);
+        }
+        mesh
+    }
+}
+
+/// Splits a polygon into triangles by clipping ears, in the plane the
+/// polygon mostly lies in. Returns indices into `points`.
+pub fn triangulate(points: &[Point3d]) -> Vec<[usize; 3]> {
+    let n = points.len();
+    if n < 3 {
+        return vec![];
+    }
+    if n == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    // newell's normal, robust for any planar polygon
+    let mut normal = Vec3d::zero();
+    for i in 0..n {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        normal += Vec3d::new(
+            (a.y - b.y) * (a.z + b.z),
+            (a.z - b.z) * (a.x + b.x),
+            (a.x - b.x) * (a.y + b.y),
+        );
+    }
+    let fan = || (1..n - 1).map(|i| [0, i, i + 1]).collect();
+    if normal.near_zero() {
+        return fan();
+    }
+
+    let cross = |o: usize, a: usize, b: usize| {
+        (points[a] - points[o])
+            .cross(&(points[b] - points[o]))
+            .dot(&normal)
+    };
+    let inside = |p: usize, a: usize, b: usize, c: usize| {
+        cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+    };
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            );
+            cross(a, b, c) > 0.0
+                && remaining
+                    .iter()
+                    .all(|&p| p == a || p == b || p == c || !inside(p, a, b, c))
+        });
+        match ear {
+            Some(i) => {
+                triangles.push([
+                    remaining[(i + m - 1) % m],
+                    remaining[i],
+                    remaining[(i + 1) % m],
+                ]);
+                remaining.remove(i);
+            }
+            // self intersecting or degenerate, give up on the rest
+            None => {
+                for i in 1..m - 1 {
+                    triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+                }
+                return triangles;
+            }
+        }
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+pub struct TriangleMesh<M>
+where
+    M: Material,
+{
+    positions: Vec<Point3d>,
+    /// one per vertex, or empty for flat shading
+    normals: Vec<Vec3d>,
+    /// one per vertex, or empty
+    uvs: Vec<(f64, f64)>,
+    faces: Vec<[usize; 3]>,
+    materials: Vec<M>,
+    /// index into `materials` for every face, or empty if there is only one
+    face_materials: Vec<usize>,
+    /// faces in the order the leaves of `tree` refer to them
+    order: Vec<usize>,
+    tree: BvhTree,
+}
+
+impl<M: Material> TriangleMesh<M> {
+    /// A flat shaded mesh of one material. Every face holds three indices
+    /// into `positions`, counterclockwise seen from outside.
+    pub fn new(positions: Vec<Point3d>, faces: Vec<[usize; 3]>, material: M) -> Self {
+        assert!(
+            faces.iter().flatten().all(|&i| i < positions.len()),
+            "mesh face refers to a vertex out of 0..{}",
+            positions.len()
+        );
+        let boxes: Vec<Aabb> = faces
+            .iter()
+            .map(|&[a, b, c]| triangle_bounding_box(positions[a], positions[b], positions[c]))
+            .collect();
+        let (tree, order) = BvhTree::new(&boxes);
+
+        Self {
+            positions,
+            normals: vec![],
+            uvs: vec![],
+            faces,
+            materials: vec![material],
+            face_materials: vec![],
+            order,
+            tree,
+        }
+    }
+
+    /// Interpolates `normals`, one per vertex, across the faces. Faces fall
+    /// back to flat shading where they interpolate to zero.
+    pub fn with_normals(mut self, normals: Vec<Vec3d>) -> Self {
+        assert_eq!(
+            normals.len(),
+            self.positions.len(),
+            "mesh needs one normal per vertex"
+        );
+        self.normals = normals
+            .into_iter()
+            .map(|n| if n.near_zero() { n } else { n.unit_vector() })
+            .collect();
+        self
+    }
+
+    /// Smooth shading without given normals, see [`vertex_normals`].
+    pub fn with_smooth_normals(self) -> Self {
+        let normals = vertex_normals(&self.positions, &self.faces);
+        self.with_normals(normals)
+    }
+
+    /// Texture coordinates, one per vertex.
+    pub fn with_uvs(mut self, uvs: Vec<(f64, f64)>) -> Self {
+        assert_eq!(
+            uvs.len(),
+            self.positions.len(),
+            "mesh needs one uv per vertex"
+        );
+        self.uvs = uvs;
+        self
+    }
+
+    /// Replaces the material of the mesh by `materials`, of which every face
+    /// uses the one at its entry in `face_materials`.
+    pub fn with_face_materials(mut self, materials: Vec<M>, face_materials: Vec<usize>) -> Self {
+        assert_eq!(
+            face_materials.len(),
+            self.faces.len(),
+            "mesh needs one material index per face"
+        );
+        assert!(
+            face_materials.iter().all(|&i| i < materials.len()),
+            "mesh face refers to a material out of 0..{}",
+            materials.len()
+        );
+        self.materials = materials;
+        self.face_materials = face_materials;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.faces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    pub fn positions(&self) -> &[Point3d] {
+        &self.positions
+    }
+
+    pub fn faces(&self) -> &[[usize; 3]] {
+        &self.faces
+    }
+
+    pub fn uvs(&self) -> &[(f64, f64)] {
+        &self.uvs
+    }
+
+    pub fn stats(&self) -> &BvhStats {
+        self.tree.stats()
+    }
+
+    fn hit_face(&self, face: usize, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let [a, b, c] = self.faces[face];
+        let (pa, pb, pc) = (self.positions[a], self.positions[b], self.positions[c]);
+        let (t, u, v) = intersect_triangle(ray, pa, pb, pc, t_min, t_max)?;
+
+        let material = match self.face_materials.get(face) {
+            Some(&i) => self.materials[i].borrow(),
+            None => self.materials[0].borrow(),
+        };
+        let geometric_normal = (pb - pa).cross(&(pc - pa)).unit_vector();
+        let mut rec =
+            HitRecord::new_with_face_normal(t, ray.at(t), geometric_normal, material, ray);
+
+        if !self.normals.is_empty() {
+            let n = (1.0 - u - v) * self.normals[a] + u * self.normals[b] + v * self.normals[c];
+            if !n.near_zero() {
+                // keep the shading normal on the side the ray came from
+                let n = n.unit_vector();
+                rec.normal = if rec.font_face { n } else { -n };
+            }
+        }
+        if self.uvs.is_empty() {
+            rec = rec
+                .with_uv(u, v)
+                .with_footprint(ray, barycentric_density(pa, pb, pc));
+        } else {
+            let (ua, va) = self.uvs[a];
+            let (ub, vb) = self.uvs[b];
+            let (uc, vc) = self.uvs[c];
+            // twice the area the face covers in the texture
+            let uv_area = ((ub - ua) * (vc - va) - (uc - ua) * (vb - va)).abs();
+            let area = (pb - pa).cross(&(pc - pa)).length();
+            rec = rec
+                .with_uv(
+                    (1.0 - u - v) * ua + u * ub + v * uc,
+                    (1.0 - u - v) * va + u * vb + v * vc,
+                )
+                .with_footprint(ray, (uv_area / area).sqrt());
+        }
+        Some(rec)
+    }
+}
+
+impl<M: Material> Hittable for TriangleMesh<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _sampler: &mut Sampler,
+    ) -> Option<HitRecord<'_>> {
+        self.tree.hit(ray, t_min, t_max, |position, closest| {
+            self.hit_face(self.order[position], ray, t_min, closest)
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.tree.bounding_box()
+    }
+}
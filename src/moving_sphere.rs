@@ -0,0 +1,82 @@
+use std::borrow::Borrow;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    sampler::Sampler,
+    sphere::{solve_sphere_equation, sphere_uv, sphere_uv_density},
+    vec3::{Point3d, Vec3d},
+};
+
+/// A sphere moving in a straight line from `center0` at `time0` to
+/// `center1` at `time1`. Before and after, it stays where it starts and
+/// ends.
+#[derive(Clone)]
+pub struct MovingSphere<M>
+where
+    M: Material,
+{
+    pub center0: Point3d,
+    pub center1: Point3d,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub material: M,
+}
+
+impl<M: Material> MovingSphere<M> {
+    pub fn new(cen0: Point3d, cen1: Point3d, time0: f64, time1: f64, r: f64, m: M) -> Self {
+        Self {
+            center0: cen0,
+            center1: cen1,
+            time0,
+            time1,
+            radius: r,
+            material: m,
+        }
+    }
+
+    pub fn center(&self, time: f64) -> Point3d {
+        if self.time1 <= self.time0 {
+            return self.center0;
+        }
+        let s = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.center0 + s * (self.center1 - self.center0)
+    }
+}
+
+impl<M: Material> Hittable for MovingSphere<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _sampler: &mut Sampler,
+    ) -> Option<HitRecord<'_>> {
+        let center = self.center(ray.time());
+        solve_sphere_equation(ray, center, self.radius, t_min, t_max).map(
+            |(root, point, outward_normal)| {
+                let (u, v) = sphere_uv(&((point - center) / self.radius.abs()));
+                HitRecord::new_with_face_normal(
+                    root,
+                    point,
+                    outward_normal,
+                    self.material.borrow(),
+                    ray,
+                )
+                .with_uv(u, v)
+                .with_footprint(ray, sphere_uv_density(self.radius))
+            },
+        )
+    }
+
+    /// Around the whole path.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3d::only(self.radius.abs());
+        let box0 = Aabb::new(self.center0 - r, self.center0 + r);
+        let box1 = Aabb::new(self.center1 - r, self.center1 + r);
+        Some(Aabb::surrounding_box(&box0, &box1))
+    }
+}
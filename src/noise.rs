@@ -0,0 +1,161 @@
+//! Noise for procedural textures. Both kinds are built from a seed and give
+//! the same values for it on every platform.
+
+use crate::{
+    sampler::Sampler,
+    vec3::{Point3d, Vec3d},
+};
+
+const POINT_COUNT: usize = 256;
+
+/// A random order of `0..POINT_COUNT`.
+fn permutation(sampler: &mut Sampler) -> [usize; POINT_COUNT] {
+    let mut perm = [0; POINT_COUNT];
+    for (i, p) in perm.iter_mut().enumerate() {
+        *p = i;
+    }
+    for i in (1..POINT_COUNT).rev() {
+        let target = (sampler.random_double() * (i + 1) as f64) as usize;
+        perm.swap(i, target);
+    }
+    perm
+}
+
+/// Hashes of lattice points, the same three permutations Perlin noise uses
+/// to pick its gradients.
+#[derive(Debug, Clone)]
+struct Lattice {
+    perm_x: [usize; POINT_COUNT],
+    perm_y: [usize; POINT_COUNT],
+    perm_z: [usize; POINT_COUNT],
+}
+
+impl Lattice {
+    fn new(sampler: &mut Sampler) -> Self {
+        Self {
+            perm_x: permutation(sampler),
+            perm_y: permutation(sampler),
+            perm_z: permutation(sampler),
+        }
+    }
+
+    fn hash(&self, i: i64, j: i64, k: i64) -> usize {
+        let wrap = |n: i64| (n & (POINT_COUNT as i64 - 1)) as usize;
+        self.perm_x[wrap(i)] ^ self.perm_y[wrap(j)] ^ self.perm_z[wrap(k)]
+    }
+}
+
+/// Gradient noise: random directions at the corners of a unit lattice,
+/// blended smoothly in between. Values lie roughly between -1 and 1, and are
+/// 0 on the lattice.
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    gradients: Vec<Vec3d>,
+    lattice: Lattice,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut sampler = Sampler::new(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| Vec3d::random_unit_vector(&mut sampler))
+            .collect();
+        Self {
+            gradients,
+            lattice: Lattice::new(&mut sampler),
+        }
+    }
+
+    pub fn noise(&self, p: &Point3d) -> f64 {
+        let (i, j, k) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let (u, v, w) = (p.x - i, p.y - j, p.z - k);
+        let (i, j, k) = (i as i64, j as i64, k as i64);
+
+        // hermite smoothing hides the lattice
+        let (uu, vv, ww) = (
+            u * u * (3.0 - 2.0 * u),
+            v * v * (3.0 - 2.0 * v),
+            w * w * (3.0 - 2.0 * w),
+        );
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let gradient = self.gradients[self.lattice.hash(i + di, j + dj, k + dk)];
+                    let (fi, fj, fk) = (di as f64, dj as f64, dk as f64);
+                    let weight = Vec3d::new(u - fi, v - fj, w - fk);
+                    accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * gradient.dot(&weight);
+                }
+            }
+        }
+        accum
+    }
+
+    /// The sum of `depth` octaves of noise, each twice as fine and half as
+    /// strong as the one before, without sign. Values lie roughly between 0
+    /// and 1.
+    pub fn turbulence(&self, p: &Point3d, depth: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut p = *p;
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            accum += weight * self.noise(&p);
+            weight *= 0.5;
+            p *= 2.0;
+        }
+        accum.abs()
+    }
+}
+
+/// Cellular noise: one random feature point in every cell of a unit
+/// lattice, and the distance to the closest of them.
+#[derive(Debug, Clone)]
+pub struct Worley {
+    /// where in its cell the feature point lies
+    offsets: Vec<Vec3d>,
+    lattice: Lattice,
+}
+
+impl Worley {
+    pub fn new(seed: u64) -> Self {
+        let mut sampler = Sampler::new(seed);
+        let offsets = (0..POINT_COUNT)
+            .map(|_| Vec3d::random(&mut sampler))
+            .collect();
+        Self {
+            offsets,
+            lattice: Lattice::new(&mut sampler),
+        }
+    }
+
+    /// Distances to the closest and the second closest feature point. The
+    /// closest is at most `3.0.sqrt()` away, in practice rarely more than 1.
+    pub fn distances(&self, p: &Point3d) -> (f64, f64) {
+        let (i, j, k) = (p.x.floor() as i64, p.y.floor() as i64, p.z.floor() as i64);
+        let (mut first, mut second) = (f64::INFINITY, f64::INFINITY);
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                for dk in -1..=1 {
+                    let (ci, cj, ck) = (i + di, j + dj, k + dk);
+                    let feature = Point3d::new(ci as f64, cj as f64, ck as f64)
+                        + self.offsets[self.lattice.hash(ci, cj, ck)];
+                    let distance = (feature - *p).length();
+                    if distance < first {
+                        second = first;
+                        first = distance;
+                    } else if distance < second {
+                        second = distance;
+                    }
+                }
+            }
+        }
+        (first, second)
+    }
+
+    pub fn noise(&self, p: &Point3d) -> f64 {
+        self.distances(p).0
+    }
+}
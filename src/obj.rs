@@ -0,0 +1,540 @@
+//! Wavefront `.obj` meshes and their `.mtl` material libraries.
+//!
+//! Faces may have any number of corners and are split into triangles by ear
+//! clipping. Every `o` or `g` starts a group, which becomes a
+//! [`TriangleMesh`](crate::mesh::TriangleMesh) of its own. Lines the renderer has no use for, like
+//! smoothing groups, lines and texture maps other than `map_Kd`, are
+//! skipped.
+//!
+//! Materials are mapped onto the ones the renderer has:
+//!
+//! - `Ke` brighter than black makes a [`DiffuseLight`],
+//! - a dissolve `d` below 1 (or `Tr` above 0), or an `illum` model with
+//!   refraction (4, 6, 7 or 9), makes a [`Dieletric`] with index `Ni`,
+//! - an `illum` model with reflection (3, 5 or 8), or a specular `Ks`
+//!   brighter than the diffuse `Kd`, makes a [`Metal`] of color `Ks` whose
+//!   fuzz follows the shininess `Ns`,
+//! - anything else is [`Diffuse`] with albedo `Kd`.
+//!
+//! A `map_Kd` image, relative to the `.mtl` file, takes the place of `Kd`.
+//! It repeats unless its options include `-clamp on`.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    hittable_list::HittableList,
+    material::{Dieletric, Diffuse, DiffuseLight, Material, Metal},
+    matrix::Matrix,
+    mesh::{triangulate, MeshData},
+    texture::{ImageTexture, WrapMode},
+    vec3::{Color3d, Point3d, Vec3d},
+};
+
+/// Faces without a material get a light grey.
+const DEFAULT_ALBEDO: f64 = 0.8;
+
+/// The triangles of one `o` or `g` group, with vertices shared by its faces.
+#[derive(Clone)]
+pub struct ObjGroup {
+    pub name: String,
+    pub mesh: MeshData,
+    /// index into [`ObjFile::materials`] for every face
+    pub face_materials: Vec<usize>,
+}
+
+pub struct ObjFile {
+    pub groups: Vec<ObjGroup>,
+    /// materials by the name they have in the `.mtl` library
+    pub materials: Vec<(String, Arc<dyn Material>)>,
+}
+
+fn invalid(file: &Path, line: usize, message: impl Into<String>) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}:{}: {}", file.display(), line, message.into()),
+    )
+}
+
+/// Numbers following the keyword of a line.
+fn numbers(file: &Path, line: usize, args: &[&str]) -> io::Result<Vec<f64>> {
+    args.iter()
+        .map(|a| match a.parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(x),
+            _ => Err(invalid(file, line, format!("invalid number '{}'", a))),
+        })
+        .collect()
+}
+
+fn vector(file: &Path, line: usize, args: &[&str], keyword: &str) -> io::Result<Vec3d> {
+    match numbers(file, line, args)?[..] {
+        [x, y, z, ..] => Ok(Vec3d::new(x, y, z)),
+        _ => Err(invalid(
+            file,
+            line,
+            format!("`{}` needs three numbers", keyword),
+        )),
+    }
+}
+
+/// Lines with comments removed and `\` continuations joined, with their
+/// line numbers.
+fn logical_lines(reader: impl BufRead, file: &Path) -> io::Result<Vec<(usize, String)>> {
+    let mut lines = vec![];
+    let mut pending: Option<(usize, String)> = None;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| invalid(file, i + 1, e.to_string()))?;
+        let line = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => &line[..],
+        };
+        let (start, mut text) = pending.take().unwrap_or((i + 1, String::new()));
+        match line.trim_end().strip_suffix('\\') {
+            Some(continued) => {
+                text.push_str(continued);
+                text.push(' ');
+                pending = Some((start, text));
+            }
+            None => {
+                text.push_str(line);
+                lines.push((start, text));
+            }
+        }
+    }
+    lines.extend(pending);
+    Ok(lines)
+}
+
+#[derive(Debug, Clone)]
+struct MtlDesc {
+    kd: Color3d,
+    ks: Color3d,
+    ke: Color3d,
+    ns: f64,
+    ni: f64,
+    d: f64,
+    illum: Option<u32>,
+    map_kd: Option<ImageTexture>,
+}
+
+impl Default for MtlDesc {
+    fn default() -> Self {
+        Self {
+            kd: Color3d::only(DEFAULT_ALBEDO),
+            ks: Color3d::zero(),
+            ke: Color3d::zero(),
+            ns: 0.0,
+            ni: 1.5,
+            d: 1.0,
+            illum: None,
+            map_kd: None,
+        }
+    }
+}
+
+fn max_component(c: Color3d) -> f64 {
+    c.x.max(c.y).max(c.z)
+}
+
+impl MtlDesc {
+    fn to_material(&self) -> Arc<dyn Material> {
+        let illum = self.illum.unwrap_or(2);
+        if max_component(self.ke) > 0.0 {
+            Arc::new(DiffuseLight::new(self.ke))
+        } else if self.d < 1.0 || matches!(illum, 4 | 6 | 7 | 9) {
+            Arc::new(Dieletric::new(if self.ni > 0.0 { self.ni } else { 1.5 }))
+        } else if matches!(illum, 3 | 5 | 8) || max_component(self.ks) > max_component(self.kd) {
+            let albedo = if max_component(self.ks) > 0.0 {
+                self.ks
+            } else {
+                self.kd
+            };
+            // roughness of a phong lobe with exponent ns
+            let fuzz = (2.0 / (self.ns.max(0.0) + 2.0)).sqrt().min(1.0);
+            match &self.map_kd {
+                Some(map) if max_component(self.ks) <= 0.0 => {
+                    Arc::new(Metal::textured(map.clone(), fuzz))
+                }
+                _ => Arc::new(Metal::new(albedo, fuzz)),
+            }
+        } else {
+            match &self.map_kd {
+                Some(map) => Arc::new(Diffuse::textured(map.clone())),
+                None => Arc::new(Diffuse::new(self.kd)),
+            }
+        }
+    }
+}
+
+/// Reads the materials of a `.mtl` library.
+pub fn load_mtl(path: impl AsRef<Path>) -> io::Result<Vec<(String, Arc<dyn Material>)>> {
+    let path = path.as_ref();
+    let reader = BufReader::new(File::open(path).map_err(|e| {
+        io::Error::new(e.kind(), format!("cannot open '{}': {}", path.display(), e))
+    })?);
+    let mut materials = vec![];
+    let mut current: Option<(String, MtlDesc)> = None;
+    for (line, text) in logical_lines(reader, path)? {
+        let mut words = text.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = words.collect();
+        if keyword == "newmtl" {
+            if args.is_empty() {
+                return Err(invalid(path, line, "`newmtl` needs a name"));
+            }
+            materials.extend(current.take().map(|(n, m)| (n, m.to_material())));
+            current = Some((args.join(" "), MtlDesc::default()));
+            continue;
+        }
+
+        let desc = match (&mut current, keyword) {
+            (Some((_, desc)), _) => desc,
+            (None, "Kd" | "Ks" | "Ke" | "Ns" | "Ni" | "d" | "Tr" | "illum" | "map_Kd") => {
+                return Err(invalid(
+                    path,
+                    line,
+                    format!("`{}` before any `newmtl`", keyword),
+                ))
+            }
+            (None, _) => continue,
+        };
+        let scalar = || match numbers(path, line, &args)?[..] {
+            [x, ..] => Ok(x),
+            _ => Err(invalid(path, line, format!("`{}` needs a number", keyword))),
+        };
+        let color = || -> io::Result<Color3d> {
+            // a single value is grey, spectral and xyz colors are not supported
+            let color = match args.first() {
+                Some(&"spectral") | Some(&"xyz") => Err(invalid(
+                    path,
+                    line,
+                    format!("only rgb values are supported for `{}`", keyword),
+                )),
+                _ => match numbers(path, line, &args)?[..] {
+                    [r, g, b, ..] => Ok(Color3d::new(r, g, b)),
+                    [v] => Ok(Color3d::only(v)),
+                    _ => Err(invalid(path, line, format!("`{}` needs a color", keyword))),
+                },
+            }?;
+            Ok(Color3d::new(
+                color.x.max(0.0),
+                color.y.max(0.0),
+                color.z.max(0.0),
+            ))
+        };
+        match keyword {
+            "Kd" => desc.kd = color()?,
+            "Ks" => desc.ks = color()?,
+            "Ke" => desc.ke = color()?,
+            "Ns" => desc.ns = scalar()?,
+            "Ni" => desc.ni = scalar()?,
+            "d" => desc.d = scalar()?,
+            "Tr" => desc.d = 1.0 - scalar()?,
+            "illum" => {
+                let value = args.first().and_then(|a| a.parse().ok());
+                if value.is_none() {
+                    return Err(invalid(path, line, "`illum` needs a whole number"));
+                }
+                desc.illum = value;
+            }
+            "map_Kd" => {
+                // options come before the file name
+                let name = match args.last() {
+                    Some(name) if !name.starts_with('-') => name,
+                    _ => return Err(invalid(path, line, "`map_Kd` needs a file name")),
+                };
+                let file = path.parent().unwrap_or_else(|| Path::new("")).join(name);
+                let map = ImageTexture::load(&file).map_err(|e| {
+                    invalid(
+                        path,
+                        line,
+                        format!("cannot load '{}': {}", file.display(), e),
+                    )
+                })?;
+                let clamp = args.windows(2).any(|w| w == ["-clamp", "on"]);
+                desc.map_kd = Some(map.with_wrap(if clamp {
+                    WrapMode::Clamp
+                } else {
+                    WrapMode::Repeat
+                }));
+            }
+            _ => {}
+        }
+    }
+    materials.extend(current.map(|(n, m)| (n, m.to_material())));
+    Ok(materials)
+}
+
+/// One corner of a face: indices into the positions, texture coordinates
+/// and normals of the file.
+type Corner = (usize, Option<usize>, Option<usize>);
+
+/// Builds a group from the corners of its faces, giving every distinct
+/// combination of position, uv and normal a vertex of its own.
+#[derive(Default)]
+struct GroupBuilder {
+    name: String,
+    vertices: HashMap<Corner, usize>,
+    corners: Vec<Corner>,
+    faces: Vec<[usize; 3]>,
+    face_materials: Vec<usize>,
+}
+
+impl GroupBuilder {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            ..Self::default()
+        }
+    }
+
+    fn vertex(&mut self, corner: Corner) -> usize {
+        let corners = &mut self.corners;
+        *self.vertices.entry(corner).or_insert_with(|| {
+            corners.push(corner);
+            corners.len() - 1
+        })
+    }
+
+    fn finish(self, positions: &[Point3d], uvs: &[(f64, f64)], normals: &[Vec3d]) -> ObjGroup {
+        let has_uvs = self.corners.iter().any(|c| c.1.is_some());
+        let has_normals = self.corners.iter().any(|c| c.2.is_some());
+        let mesh = MeshData {
+            positions: self.corners.iter().map(|c| positions[c.0]).collect(),
+            uvs: if has_uvs {
+                self.corners
+                    .iter()
+                    .map(|c| c.1.map_or((0.0, 0.0), |i| uvs[i]))
+                    .collect()
+            } else {
+                vec![]
+            },
+            // corners without a normal are shaded flat
+            normals: if has_normals {
+                self.corners
+                    .iter()
+                    .map(|c| c.2.map_or(Vec3d::zero(), |i| normals[i]))
+                    .collect()
+            } else {
+                vec![]
+            },
+            faces: self.faces,
+        };
+        ObjGroup {
+            name: self.name,
+            mesh,
+            face_materials: self.face_materials,
+        }
+    }
+}
+
+/// Resolves a 1 based or negative (counted from the end) obj index.
+fn resolve_index(
+    file: &Path,
+    line: usize,
+    text: &str,
+    len: usize,
+    what: &str,
+) -> io::Result<usize> {
+    let index = text
+        .parse::<i64>()
+        .map_err(|_| invalid(file, line, format!("invalid {} index '{}'", what, text)))?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        len as i64 + index
+    };
+    if index == 0 || resolved < 0 || resolved >= len as i64 {
+        return Err(invalid(
+            file,
+            line,
+            format!("{} index {} out of range, there are {}", what, index, len),
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+impl ObjFile {
+    /// Reads an `.obj` file and the `.mtl` libraries it names, which are
+    /// looked up next to it.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("cannot open '{}': {}", path.display(), e))
+        })?;
+        let dir = path.parent().map(PathBuf::from).unwrap_or_default();
+        Self::read(BufReader::new(file), path, &dir)
+    }
+
+    /// Reads an `.obj` from `reader`, `file` is only used in error messages
+    /// and material libraries are looked up in `dir`.
+    pub fn read(reader: impl BufRead, file: &Path, dir: &Path) -> io::Result<Self> {
+        let mut positions: Vec<Point3d> = vec![];
+        let mut uvs: Vec<(f64, f64)> = vec![];
+        let mut normals: Vec<Vec3d> = vec![];
+        let mut library: Vec<(String, Arc<dyn Material>)> = vec![];
+        let mut used: HashMap<String, usize> = HashMap::new();
+        let mut materials: Vec<(String, Arc<dyn Material>)> = vec![];
+        let mut current_material: Option<usize> = None;
+
+        let mut groups = vec![];
+        let mut group = GroupBuilder::new(String::new());
+
+        for (line, text) in logical_lines(reader, file)? {
+            let mut words = text.split_whitespace();
+            let keyword = match words.next() {
+                Some(keyword) => keyword,
+                None => continue,
+            };
+            let args: Vec<&str> = words.collect();
+            match keyword {
+                "v" => positions.push(vector(file, line, &args, "v")?),
+                "vn" => normals.push(vector(file, line, &args, "vn")?),
+                "vt" => match numbers(file, line, &args)?[..] {
+                    [u, v, ..] => uvs.push((u, v)),
+                    [u] => uvs.push((u, 0.0)),
+                    _ => return Err(invalid(file, line, "`vt` needs a number")),
+                },
+                "f" => {
+                    if args.len() < 3 {
+                        return Err(invalid(file, line, "a face needs at least three corners"));
+                    }
+                    let mut corners = Vec::with_capacity(args.len());
+                    for arg in &args {
+                        let mut parts = arg.split('/');
+                        let p = resolve_index(
+                            file,
+                            line,
+                            parts.next().unwrap_or(""),
+                            positions.len(),
+                            "vertex",
+                        )?;
+                        let t = match parts.next() {
+                            Some(t) if !t.is_empty() => {
+                                Some(resolve_index(file, line, t, uvs.len(), "texture")?)
+                            }
+                            _ => None,
+                        };
+                        let n = match parts.next() {
+                            Some(n) if !n.is_empty() => {
+                                Some(resolve_index(file, line, n, normals.len(), "normal")?)
+                            }
+                            _ => None,
+                        };
+                        corners.push((p, t, n));
+                    }
+
+                    let material = match current_material {
+                        Some(m) => m,
+                        None => {
+                            // faces before any `usemtl`
+                            let m = *used.entry(String::new()).or_insert_with(|| {
+                                materials.push((
+                                    String::new(),
+                                    Arc::new(Diffuse::new(Color3d::only(DEFAULT_ALBEDO))),
+                                ));
+                                materials.len() - 1
+                            });
+                            current_material = Some(m);
+                            m
+                        }
+                    };
+                    let points: Vec<Point3d> = corners.iter().map(|c| positions[c.0]).collect();
+                    for [a, b, c] in triangulate(&points) {
+                        let face = [
+                            group.vertex(corners[a]),
+                            group.vertex(corners[b]),
+                            group.vertex(corners[c]),
+                        ];
+                        group.faces.push(face);
+                        group.face_materials.push(material);
+                    }
+                }
+                "o" | "g" => {
+                    let name = args.join(" ");
+                    let previous = std::mem::replace(&mut group, GroupBuilder::new(name));
+                    if !previous.faces.is_empty() {
+                        groups.push(previous.finish(&positions, &uvs, &normals));
+                    }
+                }
+                "mtllib" => {
+                    // names may contain spaces, or there may be several
+                    let whole = dir.join(args.join(" "));
+                    let files = if args.len() > 1 && !whole.exists() {
+                        args.iter().map(|a| dir.join(a)).collect()
+                    } else {
+                        vec![whole]
+                    };
+                    for mtl in files {
+                        let materials =
+                            load_mtl(&mtl).map_err(|e| invalid(file, line, e.to_string()))?;
+                        library.extend(materials);
+                    }
+                }
+                "usemtl" => {
+                    let name = args.join(" ");
+                    let index = match used.get(&name) {
+                        Some(&index) => index,
+                        None => {
+                            let material = library
+                                .iter()
+                                .rev()
+                                .find(|(n, _)| *n == name)
+                                .map(|(_, m)| m.clone())
+                                .ok_or_else(|| {
+                                    invalid(file, line, format!("unknown material '{}'", name))
+                                })?;
+                            materials.push((name.clone(), material));
+                            used.insert(name, materials.len() - 1);
+                            materials.len() - 1
+                        }
+                    };
+                    current_material = Some(index);
+                }
+                _ => {}
+            }
+        }
+        if !group.faces.is_empty() {
+            groups.push(group.finish(&positions, &uvs, &normals));
+        }
+
+        Ok(Self { groups, materials })
+    }
+
+    /// Uses `material` for every face instead of the ones of the file.
+    pub fn with_material(mut self, material: Arc<dyn Material>) -> Self {
+        self.materials = vec![(String::new(), material)];
+        for group in self.groups.iter_mut() {
+            group.face_materials.iter_mut().for_each(|m| *m = 0);
+        }
+        self
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.groups.iter().map(|g| g.mesh.faces.len()).sum()
+    }
+
+    /// Adds every group to `world` as a mesh, placed by `transform` if any.
+    pub fn add_to(self, world: &mut HittableList, transform: Option<&Matrix>) {
+        let materials: Vec<Arc<dyn Material>> =
+            self.materials.into_iter().map(|(_, m)| m).collect();
+        for mut group in self.groups {
+            if let Some(transform) = transform {
+                group.mesh.transform(transform);
+            }
+            let mesh = group
+                .mesh
+                .into_mesh(materials[0].clone())
+                .with_face_materials(materials.clone(), group.face_materials);
+            world.push(Box::new(mesh));
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::borrow::Borrow;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    sampler::Sampler,
+    vec3::{Point3d, Vec3d},
+};
+
+/// Infinite plane through `point`, facing `normal`. It has no bounding box,
+/// so bvhs test it separately from everything else. Texture coordinates are
+/// distances from `point` along two directions in the plane, so textures
+/// repeat every unit.
+#[derive(Clone)]
+pub struct Plane<M>
+where
+    M: Material,
+{
+    pub point: Point3d,
+    pub normal: Vec3d,
+    pub material: M,
+    /// directions of u and v
+    tangent: Vec3d,
+    bitangent: Vec3d,
+}
+
+impl<M: Material> Plane<M> {
+    pub fn new(point: Point3d, normal: Vec3d, m: M) -> Self {
+        let normal = normal.unit_vector();
+        // any axis that is not along the normal does
+        let axis = if normal.x.abs() > 0.9 {
+            Vec3d::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3d::new(1.0, 0.0, 0.0)
+        };
+        let bitangent = normal.cross(&axis).unit_vector();
+        Self {
+            point,
+            normal,
+            material: m,
+            tangent: bitangent.cross(&normal),
+            bitangent,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Plane<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _sampler: &mut Sampler,
+    ) -> Option<HitRecord<'_>> {
+        let denom = self.normal.dot(&ray.direction());
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.point - ray.origin()).dot(&self.normal) / denom;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let planar = point - self.point;
+        Some(
+            HitRecord::new_with_face_normal(t, point, self.normal, self.material.borrow(), ray)
+                .with_uv(planar.dot(&self.tangent), planar.dot(&self.bitangent))
+                .with_footprint(ray, 1.0),
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
@@ -0,0 +1,486 @@
+//! Stanford `.ply` meshes, in ascii or binary of either byte order.
+//!
+//! Vertices are read from the `x`, `y` and `z` properties of the `vertex`
+//! element, normals from `nx`, `ny` and `nz` and texture coordinates from
+//! `u` and `v` (or `s` and `t`) if there are any. Faces are the
+//! `vertex_indices` lists of the `face` element, polygons are split into
+//! triangles. Everything else is skipped.
+
+use std::{convert::TryInto, fs, io, path::Path};
+
+use crate::{
+    mesh::{triangulate, MeshData},
+    vec3::{Point3d, Vec3d},
+};
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scalar {
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Scalar::Int8,
+            "uchar" | "uint8" => Scalar::Uint8,
+            "short" | "int16" => Scalar::Int16,
+            "ushort" | "uint16" => Scalar::Uint16,
+            "int" | "int32" => Scalar::Int32,
+            "uint" | "uint32" => Scalar::Uint32,
+            "float" | "float32" => Scalar::Float32,
+            "double" | "float64" => Scalar::Float64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::Int8 | Scalar::Uint8 => 1,
+            Scalar::Int16 | Scalar::Uint16 => 2,
+            Scalar::Int32 | Scalar::Uint32 | Scalar::Float32 => 4,
+            Scalar::Float64 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Property {
+    Scalar(String, Scalar),
+    /// name, type of the length and type of the items
+    List(String, Scalar, Scalar),
+}
+
+impl Property {
+    fn name(&self) -> &str {
+        match self {
+            Property::Scalar(name, _) | Property::List(name, _, _) => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+    /// offset of the data after `end_header`
+    body: usize,
+}
+
+fn parse_header(data: &[u8]) -> io::Result<Header> {
+    if !data.starts_with(b"ply") {
+        return Err(invalid("not a ply file"));
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = vec![];
+    let mut offset = 0;
+    let mut number = 0;
+    loop {
+        number += 1;
+        let rest = &data[offset..];
+        let end = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| invalid("the ply header has no `end_header`"))?;
+        offset += end + 1;
+        let line = std::str::from_utf8(&rest[..end])
+            .map_err(|_| invalid(format!("ply header line {} is not text", number)))?;
+        let error = |message: &str| invalid(format!("ply header line {}: {}", number, message));
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["ply"] if number == 1 => {}
+            _ if number == 1 => return Err(invalid("not a ply file")),
+            ["format", kind, version] => {
+                if version != "1.0" {
+                    return Err(error(&format!("unsupported ply version {}", version)));
+                }
+                format = Some(match kind {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::LittleEndian,
+                    "binary_big_endian" => Format::BigEndian,
+                    _ => return Err(error(&format!("unknown format '{}'", kind))),
+                });
+            }
+            ["element", name, count] => {
+                let count = count
+                    .parse()
+                    .map_err(|_| error(&format!("invalid element count '{}'", count)))?;
+                elements.push(Element {
+                    name: name.to_string(),
+                    count,
+                    properties: vec![],
+                });
+            }
+            ["property", "list", count, item, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("property before any element"))?;
+                let (count, item) = match (Scalar::parse(count), Scalar::parse(item)) {
+                    (Some(count), Some(item)) => (count, item),
+                    _ => return Err(error("unknown property type")),
+                };
+                if matches!(count, Scalar::Float32 | Scalar::Float64) {
+                    return Err(error("list lengths must be integers"));
+                }
+                element
+                    .properties
+                    .push(Property::List(name.to_string(), count, item));
+            }
+            ["property", kind, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("property before any element"))?;
+                let kind = Scalar::parse(kind)
+                    .ok_or_else(|| error(&format!("unknown property type '{}'", kind)))?;
+                element
+                    .properties
+                    .push(Property::Scalar(name.to_string(), kind));
+            }
+            ["end_header"] => {
+                let format = format.ok_or_else(|| invalid("the ply header has no format"))?;
+                return Ok(Header {
+                    format,
+                    elements,
+                    body: offset,
+                });
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(error(&format!("unexpected '{}'", line.trim()))),
+        }
+    }
+}
+
+/// The values of the body, one after the other whatever the format.
+struct Values<'a> {
+    format: Format,
+    data: &'a [u8],
+    offset: usize,
+    /// whitespace separated words of an ascii body
+    words: std::str::SplitAsciiWhitespace<'a>,
+}
+
+impl<'a> Values<'a> {
+    fn new(format: Format, data: &'a [u8]) -> io::Result<Self> {
+        let text = if format == Format::Ascii {
+            std::str::from_utf8(data).map_err(|_| invalid("the ascii ply body is not text"))?
+        } else {
+            ""
+        };
+        Ok(Self {
+            format,
+            data,
+            offset: 0,
+            words: text.split_ascii_whitespace(),
+        })
+    }
+
+    fn next(&mut self, kind: Scalar) -> io::Result<f64> {
+        if self.format == Format::Ascii {
+            let word = self
+                .words
+                .next()
+                .ok_or_else(|| invalid("the ply data ends early"))?;
+            return word
+                .parse()
+                .map_err(|_| invalid(format!("invalid number '{}' in the ply data", word)));
+        }
+
+        let size = kind.size();
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + size)
+            .ok_or_else(|| invalid("the ply data ends early"))?;
+        self.offset += size;
+        let big = self.format == Format::BigEndian;
+        macro_rules! decode {
+            ($t: ty) => {{
+                let bytes = bytes.try_into().unwrap();
+                (if big {
+                    <$t>::from_be_bytes(bytes)
+                } else {
+                    <$t>::from_le_bytes(bytes)
+                }) as f64
+            }};
+        }
+        Ok(match kind {
+            Scalar::Int8 => decode!(i8),
+            Scalar::Uint8 => decode!(u8),
+            Scalar::Int16 => decode!(i16),
+            Scalar::Uint16 => decode!(u16),
+            Scalar::Int32 => decode!(i32),
+            Scalar::Uint32 => decode!(u32),
+            Scalar::Float32 => decode!(f32),
+            Scalar::Float64 => decode!(f64),
+        })
+    }
+
+    /// Reads a value of `property`, lists are skipped and read as 0.
+    fn next_property(&mut self, property: &Property) -> io::Result<f64> {
+        match property {
+            Property::Scalar(_, kind) => self.next(*kind),
+            Property::List(_, count, item) => {
+                for _ in 0..self.next_index(*count)? {
+                    self.next(*item)?;
+                }
+                Ok(0.0)
+            }
+        }
+    }
+
+    /// A list length or vertex index.
+    fn next_index(&mut self, kind: Scalar) -> io::Result<usize> {
+        let value = self.next(kind)?;
+        if value < 0.0 || value.fract() != 0.0 || value > u32::MAX as f64 {
+            return Err(invalid(format!("invalid index {} in the ply data", value)));
+        }
+        Ok(value as usize)
+    }
+}
+
+/// Reads a ply file that is entirely in `data`.
+pub fn read_ply(data: &[u8]) -> io::Result<MeshData> {
+    let header = parse_header(data)?;
+    let mut values = Values::new(header.format, &data[header.body..])?;
+
+    let mut mesh = MeshData::default();
+    let mut polygons: Vec<Vec<usize>> = vec![];
+    let mut has_vertices = false;
+    for element in &header.elements {
+        let find = |names: &[&str]| {
+            element
+                .properties
+                .iter()
+                .position(|p| matches!(p, Property::Scalar(..)) && names.contains(&p.name()))
+        };
+        match element.name.as_str() {
+            "vertex" => {
+                if has_vertices {
+                    return Err(invalid("the ply file has two vertex elements"));
+                }
+                has_vertices = true;
+                let position = match (find(&["x"]), find(&["y"]), find(&["z"])) {
+                    (Some(x), Some(y), Some(z)) => [x, y, z],
+                    _ => return Err(invalid("ply vertices need x, y and z properties")),
+                };
+                let normal = match (find(&["nx"]), find(&["ny"]), find(&["nz"])) {
+                    (Some(x), Some(y), Some(z)) => Some([x, y, z]),
+                    _ => None,
+                };
+                let uv = match (
+                    find(&["u", "s", "texture_u", "texture_s"]),
+                    find(&["v", "t", "texture_v", "texture_t"]),
+                ) {
+                    (Some(u), Some(v)) => Some([u, v]),
+                    _ => None,
+                };
+
+                let mut row = vec![0.0; element.properties.len()];
+                for _ in 0..element.count {
+                    for (value, property) in row.iter_mut().zip(&element.properties) {
+                        *value = values.next_property(property)?;
+                    }
+                    let [x, y, z] = position.map(|i| row[i]);
+                    if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+                        return Err(invalid("ply vertex positions must be finite"));
+                    }
+                    mesh.positions.push(Point3d::new(x, y, z));
+                    if let Some([x, y, z]) = normal {
+                        mesh.normals.push(Vec3d::new(row[x], row[y], row[z]));
+                    }
+                    if let Some([u, v]) = uv {
+                        mesh.uvs.push((row[u], row[v]));
+                    }
+                }
+            }
+            "face" => {
+                let indices = element
+                    .properties
+                    .iter()
+                    .position(|p| {
+                        matches!(p, Property::List(..))
+                            && matches!(p.name(), "vertex_indices" | "vertex_index")
+                    })
+                    .ok_or_else(|| invalid("ply faces need a vertex_indices list"))?;
+                for _ in 0..element.count {
+                    for (i, property) in element.properties.iter().enumerate() {
+                        match property {
+                            Property::List(_, count, item) if i == indices => {
+                                let len = values.next_index(*count)?;
+                                let mut polygon = Vec::with_capacity(len.min(64));
+                                for _ in 0..len {
+                                    polygon.push(values.next_index(*item)?);
+                                }
+                                polygons.push(polygon);
+                            }
+                            _ => {
+                                values.next_property(property)?;
+                            }
+                        }
+                    }
+                }
+            }
+            // without properties there is nothing to skip, however many
+            _ if element.properties.is_empty() => {}
+            _ => {
+                for _ in 0..element.count {
+                    for property in &element.properties {
+                        values.next_property(property)?;
+                    }
+                }
+            }
+        }
+    }
+
+    for polygon in polygons {
+        if let Some(&i) = polygon.iter().find(|&&i| i >= mesh.positions.len()) {
+            return Err(invalid(format!(
+                "ply face refers to vertex {}, there are {}",
+                i,
+                mesh.positions.len()
+            )));
+        }
+        let points: Vec<Point3d> = polygon.iter().map(|&i| mesh.positions[i]).collect();
+        for [a, b, c] in triangulate(&points) {
+            mesh.faces.push([polygon[a], polygon[b], polygon[c]]);
+        }
+    }
+    Ok(mesh)
+}
+
+pub fn load_ply(path: impl AsRef<Path>) -> io::Result<MeshData> {
+    let path = path.as_ref();
+    let data = fs::read(path).map_err(|e| {
+        io::Error::new(e.kind(), format!("cannot open '{}': {}", path.display(), e))
+    })?;
+    read_ply(&data).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: [[f32; 3]; 4] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ];
+
+    fn header(format: &str) -> String {
+        format!(
+            "ply\nformat {} 1.0\ncomment a unit square\nelement vertex 4\n\
+             property float x\nproperty float y\nproperty float z\n\
+             element face 1\nproperty list uchar int vertex_indices\nend_header\n",
+            format
+        )
+    }
+
+    /// The square in binary, with `last` as the index of its fourth corner.
+    fn binary(big: bool, last: i32) -> Vec<u8> {
+        let format = if big {
+            "binary_big_endian"
+        } else {
+            "binary_little_endian"
+        };
+        let mut data = header(format).into_bytes();
+        for value in SQUARE.iter().flatten() {
+            data.extend(if big {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            });
+        }
+        data.push(4);
+        for index in [0, 1, 2, last] {
+            data.extend(if big {
+                index.to_be_bytes()
+            } else {
+                index.to_le_bytes()
+            });
+        }
+        data
+    }
+
+    fn assert_square(mesh: &MeshData) {
+        let positions: Vec<Point3d> = SQUARE
+            .iter()
+            .map(|p| Point3d::new(p[0] as f64, p[1] as f64, p[2] as f64))
+            .collect();
+        assert_eq!(mesh.positions, positions);
+        assert!(mesh.normals.is_empty() && mesh.uvs.is_empty());
+        assert_eq!(mesh.faces.len(), 2);
+        for face in &mesh.faces {
+            assert!(face.iter().all(|&i| i < 4));
+        }
+    }
+
+    #[test]
+    fn reads_ascii() {
+        let data = header("ascii") + "0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 3\n";
+        assert_square(&read_ply(data.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn reads_binary_little_endian() {
+        assert_square(&read_ply(&binary(false, 3)).unwrap());
+    }
+
+    #[test]
+    fn reads_binary_big_endian() {
+        assert_square(&read_ply(&binary(true, 3)).unwrap());
+    }
+
+    #[test]
+    fn rejects_truncated_bodies() {
+        let data = header("ascii") + "0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2\n";
+        assert!(read_ply(data.as_bytes()).is_err());
+        for big in [false, true] {
+            let data = binary(big, 3);
+            for len in [data.len() - 1, data.len() - 17, header("ascii").len() + 6] {
+                let error = read_ply(&data[..len]).unwrap_err();
+                assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_face_indices() {
+        let data = header("ascii") + "0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 4\n";
+        assert!(read_ply(data.as_bytes()).is_err());
+        assert!(read_ply(&binary(false, 4)).is_err());
+        assert!(read_ply(&binary(true, 7)).is_err());
+        assert!(read_ply(&binary(false, -1)).is_err());
+    }
+
+    #[test]
+    fn rejects_headers_without_end() {
+        let data = "ply\nformat ascii 1.0\nelement vertex 0\n";
+        assert!(read_ply(data.as_bytes()).is_err());
+        assert!(read_ply(b"solid cube\n").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use std::borrow::Borrow;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    sampler::Sampler,
+    vec3::{Point3d, Vec3d},
+};
+
+/// The parallelogram spanned by `u` and `v` from the corner `q`, without a
+/// material so that compound shapes can share theirs. The outward normal is
+/// `u × v`.
+#[derive(Debug, Clone, Copy)]
+pub struct QuadShape {
+    pub q: Point3d,
+    pub u: Vec3d,
+    pub v: Vec3d,
+    normal: Vec3d,
+    d: f64,
+    w: Vec3d,
+}
+
+impl QuadShape {
+    /// Panics unless `u` and `v` [span](QuadShape::spans) a parallelogram.
+    pub fn new(q: Point3d, u: Vec3d, v: Vec3d) -> Self {
+        assert!(
+            Self::spans(&u, &v),
+            "the sides of a quad must not be zero or parallel, got {:?} and {:?}",
+            u,
+            v
+        );
+        let n = u.cross(&v);
+        let normal = n.unit_vector();
+        Self {
+            q,
+            u,
+            v,
+            normal,
+            d: normal.dot(&q),
+            w: n / n.dot(&n),
+        }
+    }
+
+    /// Whether `u` and `v` span a parallelogram with an area, and so a
+    /// plane with a normal: neither is zero and they are not parallel.
+    pub fn spans(u: &Vec3d, v: &Vec3d) -> bool {
+        !u.cross(v).near_zero()
+    }
+
+    /// How fast the coordinates along `u` and `v` change over the surface,
+    /// on average, see [`HitRecord::with_footprint`].
+    pub fn uv_density(&self) -> f64 {
+        1.0 / self.u.cross(&self.v).length().sqrt()
+    }
+
+    /// Distance, point, outward normal and the coordinates along `u` and `v`
+    /// of the hit with `ray`, if any.
+    pub fn intersect(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<(f64, Point3d, Vec3d, (f64, f64))> {
+        let denom = self.normal.dot(&ray.direction());
+        // parallel to the plane
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(&ray.origin())) / denom;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        // coordinates of the hit in the frame of u and v
+        let point = ray.at(t);
+        let planar = point - self.q;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some((t, point, self.normal, (alpha, beta)))
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        let corners = [self.q + self.u, self.q + self.v, self.q + self.u + self.v];
+        let bbox = corners.iter().fold(Aabb::new(self.q, self.q), |acc, c| {
+            Aabb::surrounding_box(&acc, &Aabb::new(*c, *c))
+        });
+        // a flat box would be missed by rays in its plane
+        let delta = 0.0001;
+        let pad = Vec3d::new(
+            if bbox.maximum.x - bbox.minimum.x < delta {
+                delta
+            } else {
+                0.0
+            },
+            if bbox.maximum.y - bbox.minimum.y < delta {
+                delta
+            } else {
+                0.0
+            },
+            if bbox.maximum.z - bbox.minimum.z < delta {
+                delta
+            } else {
+                0.0
+            },
+        );
+        Aabb::new(bbox.minimum - pad, bbox.maximum + pad)
+    }
+}
+
+/// A parallelogram, which covers the axis aligned rectangles of the book
+/// when `u` and `v` are along the axes.
+#[derive(Clone)]
+pub struct Quad<M>
+where
+    M: Material,
+{
+    pub shape: QuadShape,
+    pub material: M,
+}
+
+impl<M: Material> Quad<M> {
+    pub fn new(q: Point3d, u: Vec3d, v: Vec3d, m: M) -> Self {
+        Self {
+            shape: QuadShape::new(q, u, v),
+            material: m,
+        }
+    }
+}
+
+impl<M: Material> Hittable for Quad<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _sampler: &mut Sampler,
+    ) -> Option<HitRecord<'_>> {
+        self.shape
+            .intersect(ray, t_min, t_max)
+            .map(|(t, point, outward_normal, (u, v))| {
+                HitRecord::new_with_face_normal(
+                    t,
+                    point,
+                    outward_normal,
+                    self.material.borrow(),
+                    ray,
+                )
+                .with_uv(u, v)
+                .with_footprint(ray, self.shape.uv_density())
+            })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.shape.bounding_box())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_only_sides_with_an_area() {
+        let (x, y) = (Vec3d::new(1.0, 0.0, 0.0), Vec3d::new(0.0, 1.0, 0.0));
+        assert!(QuadShape::spans(&x, &y));
+        assert!(QuadShape::spans(&(1e-6 * x), &(1e-6 * y)));
+        assert!(!QuadShape::spans(&x, &(-2.0 * x)));
+        assert!(!QuadShape::spans(&x, &Vec3d::zero()));
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be zero or parallel")]
+    fn rejects_parallel_sides() {
+        let x = Vec3d::new(1.0, 0.0, 0.0);
+        QuadShape::new(Point3d::zero(), x, 3.0 * x);
+    }
+}
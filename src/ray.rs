@@ -3,16 +3,29 @@ use crate::vec3::{Point3d, Vec3d};
 pub struct Ray {
     orig: Point3d,
     dir: Vec3d,
+    tm: f64,
+    /// how much wider the ray gets per unit of distance, as a cone
+    spread: f64,
 }
 
 impl Ray {
-    pub fn new(origin: Point3d, direction: Vec3d) -> Self {
+    /// Moving objects are where they are at `time`.
+    pub fn new(origin: Point3d, direction: Vec3d, time: f64) -> Self {
         Self {
             orig: origin,
             dir: direction,
+            tm: time,
+            spread: 0.0,
         }
     }
 
+    /// A ray standing for a cone, as wide as a pixel for camera rays, so
+    /// that textures can be filtered over what the pixel sees.
+    pub fn with_spread(mut self, spread: f64) -> Self {
+        self.spread = spread;
+        self
+    }
+
     pub fn origin(&self) -> Point3d {
         self.orig
     }
@@ -21,6 +34,14 @@ impl Ray {
         self.dir
     }
 
+    pub fn time(&self) -> f64 {
+        self.tm
+    }
+
+    pub fn spread(&self) -> f64 {
+        self.spread
+    }
+
     pub fn at(&self, t: f64) -> Point3d {
         self.orig + self.dir * t
     }
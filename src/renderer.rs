@@ -0,0 +1,326 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::{
+    environment::Environment, framebuffer::Framebuffer, hittable::Hittable, ray::Ray,
+    sampler::Sampler, scenes::Scene, tonemap::ToneMapping, vec3::Color3d,
+};
+
+const DEFAULT_TILE_SIZE: usize = 32;
+
+/// Radiance arriving along `r`, rays that escape the scene see `environment`.
+pub fn ray_color<H: Hittable + ?Sized>(
+    r: &Ray,
+    world: &H,
+    environment: &dyn Environment,
+    depth: i32,
+    sampler: &mut Sampler,
+) -> Color3d {
+    trace(r, world, environment, depth, None, sampler)
+}
+
+// power heuristic weight of a sample taken with density `a` when `b` could
+// have picked it too
+fn mis_weight(a: f64, b: f64) -> f64 {
+    if a <= 0.0 {
+        return 0.0;
+    }
+    a * a / (a * a + b * b)
+}
+
+/// `scatter_pdf` is the density the previous bounce picked `r` with, if that
+/// bounce also sampled the environment directly. The environment reached
+/// either way is then weighted by multiple importance sampling, so neither
+/// is counted twice.
+fn trace<H: Hittable + ?Sized>(
+    r: &Ray,
+    world: &H,
+    environment: &dyn Environment,
+    depth: i32,
+    scatter_pdf: Option<f64>,
+    sampler: &mut Sampler,
+) -> Color3d {
+    if depth <= 0 {
+        return Color3d::only(0.0);
+    }
+
+    let result = match world.hit(r, 0.001, f64::INFINITY, sampler) {
+        Some(result) => result,
+        None => {
+            let radiance = environment.radiance(&r.direction());
+            return match scatter_pdf {
+                Some(pdf) => radiance * mis_weight(pdf, environment.pdf(&r.direction())),
+                None => radiance,
+            };
+        }
+    };
+
+    let emitted = result.material.emitted(r, &result);
+    let (attenuation, scattered) = match result.material.scatter(r, &result, sampler) {
+        Some(scatter) => scatter,
+        None => return emitted,
+    };
+
+    let mut color = emitted;
+    let scatter_pdf = result
+        .material
+        .scattering(r, &result, &scattered.direction())
+        .map(|(_, pdf)| pdf);
+    if scatter_pdf.is_some() {
+        if let Some((direction, radiance, light_pdf)) = environment.sample(sampler) {
+            if let Some((value, pdf)) = result.material.scattering(r, &result, &direction) {
+                let shadow = Ray::new(result.p, direction, r.time());
+                if pdf > 0.0 && world.hit(&shadow, 0.001, f64::INFINITY, sampler).is_none() {
+                    color += value * radiance * (mis_weight(light_pdf, pdf) / light_pdf);
+                }
+            }
+        }
+    }
+
+    color
+        + attenuation
+            * trace(
+                &scattered,
+                world,
+                environment,
+                depth - 1,
+                scatter_pdf,
+                sampler,
+            )
+}
+
+struct Tile {
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+}
+
+pub struct Renderer {
+    image_width: usize,
+    image_height: usize,
+    samples_per_pixel: i32,
+    max_depth: i32,
+    tile_size: usize,
+    threads: usize,
+    seed: Option<u64>,
+    tone_mapping: ToneMapping,
+    progress: Option<Box<Progress>>,
+}
+
+/// Called with the number of finished tiles and the number of all tiles.
+pub type Progress = dyn Fn(usize, usize) + Send + Sync;
+
+impl Renderer {
+    /// Panics if the image is less than 2 pixels wide or high, or if there
+    /// is not at least one sample per pixel and one bounce per ray.
+    pub fn new(
+        image_width: usize,
+        image_height: usize,
+        samples_per_pixel: i32,
+        max_depth: i32,
+    ) -> Self {
+        assert!(
+            image_width >= 2 && image_height >= 2,
+            "images must be at least 2x2 pixels, got {}x{}",
+            image_width,
+            image_height
+        );
+        assert!(
+            samples_per_pixel > 0,
+            "samples per pixel must be greater than 0, got {}",
+            samples_per_pixel
+        );
+        assert!(
+            max_depth > 0,
+            "the maximum depth must be greater than 0, got {}",
+            max_depth
+        );
+        Self {
+            image_width,
+            image_height,
+            samples_per_pixel,
+            max_depth,
+            tile_size: DEFAULT_TILE_SIZE,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            seed: None,
+            tone_mapping: ToneMapping::default(),
+            progress: None,
+        }
+    }
+
+    pub fn with_tile_size(mut self, tile_size: usize) -> Self {
+        self.tile_size = tile_size.max(1);
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Every tile gets its own sampler seeded from `seed` and the tile index,
+    /// so the image does not depend on which thread rendered a tile.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Tone mapping of the framebuffer returned by [`Renderer::render`].
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    /// Reports every finished tile to `progress`, from the render threads.
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(usize, usize) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    fn tiles(&self) -> Vec<Tile> {
+        let mut tiles = vec![];
+        for y0 in (0..self.image_height).step_by(self.tile_size) {
+            for x0 in (0..self.image_width).step_by(self.tile_size) {
+                tiles.push(Tile {
+                    x0,
+                    y0,
+                    x1: (x0 + self.tile_size).min(self.image_width),
+                    y1: (y0 + self.tile_size).min(self.image_height),
+                });
+            }
+        }
+        tiles
+    }
+
+    fn render_tile(&self, tile: &Tile, scene: &Scene, sampler: &mut Sampler) -> Vec<Color3d> {
+        let mut pixels = Vec::with_capacity((tile.x1 - tile.x0) * (tile.y1 - tile.y0));
+        let spread = scene.camera.pixel_spread(self.image_height);
+        for y in tile.y0..tile.y1 {
+            // rows are stored from the top, the camera counts from the bottom
+            let j = self.image_height - 1 - y;
+            for i in tile.x0..tile.x1 {
+                let mut color = Color3d::only(0.0);
+                for _ in 0..self.samples_per_pixel {
+                    let u = (i as f64 + sampler.random_double()) / (self.image_width - 1) as f64;
+                    let v = (j as f64 + sampler.random_double()) / (self.image_height - 1) as f64;
+
+                    let r = scene.camera.get_ray(u, v, sampler).with_spread(spread);
+                    color += ray_color(
+                        &r,
+                        scene.world.as_ref(),
+                        scene.environment.as_ref(),
+                        self.max_depth,
+                        sampler,
+                    );
+                }
+                pixels.push(color);
+            }
+        }
+        pixels
+    }
+
+    pub fn render(&self, scene: &Scene) -> Framebuffer {
+        let tiles = self.tiles();
+        let next_tile = AtomicUsize::new(0);
+        let finished = AtomicUsize::new(0);
+
+        let rendered: Vec<(usize, Vec<Color3d>)> = thread::scope(|s| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut done = vec![];
+                        loop {
+                            let index = next_tile.fetch_add(1, Ordering::Relaxed);
+                            if index >= tiles.len() {
+                                break;
+                            }
+                            let mut sampler = match self.seed {
+                                Some(seed) => Sampler::new(tile_seed(seed, index)),
+                                None => Sampler::from_entropy(),
+                            };
+                            let pixels = self.render_tile(&tiles[index], scene, &mut sampler);
+                            done.push((index, pixels));
+                            let finished = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                            if let Some(progress) = &self.progress {
+                                progress(finished, tiles.len());
+                            }
+                        }
+                        done
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("render thread panicked"))
+                .collect()
+        });
+
+        let mut framebuffer = Framebuffer::new(self.image_width, self.image_height);
+        framebuffer.set_tone_mapping(self.tone_mapping);
+        for (index, pixels) in rendered {
+            let tile = &tiles[index];
+            let coords = (tile.y0..tile.y1).flat_map(|y| (tile.x0..tile.x1).map(move |x| (x, y)));
+            for ((x, y), color) in coords.zip(pixels) {
+                framebuffer.add_samples(x, y, color, self.samples_per_pixel as u32);
+            }
+        }
+        framebuffer
+    }
+}
+
+// splitmix64, spreads neighbouring tile indices over unrelated seeds
+fn tile_seed(seed: u64, index: usize) -> u64 {
+    let mut z = seed.wrapping_add((index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenes::builtin_scenes;
+
+    fn render_with_threads(threads: usize) -> Framebuffer {
+        let builtin = builtin_scenes()
+            .iter()
+            .find(|scene| scene.name == "cornell-smoke")
+            .unwrap();
+        let scene = builtin.build(1.0, &mut Sampler::new(7));
+        Renderer::new(24, 24, 4, 8)
+            .with_tile_size(8)
+            .with_threads(threads)
+            .with_seed(7)
+            .render(&scene)
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 2x2 pixels")]
+    fn rejects_images_one_pixel_wide() {
+        Renderer::new(1, 24, 4, 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "samples per pixel")]
+    fn rejects_negative_samples_per_pixel() {
+        Renderer::new(24, 24, -1, 8);
+    }
+
+    #[test]
+    fn seeded_render_does_not_depend_on_thread_count() {
+        let single = render_with_threads(1);
+        let multi = render_with_threads(4);
+        for y in 0..single.height() {
+            for x in 0..single.width() {
+                assert_eq!(single.get(x, y), multi.get(x, y), "pixel ({}, {})", x, y);
+            }
+        }
+    }
+}
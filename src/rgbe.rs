@@ -0,0 +1,260 @@
+//! Radiance rgbe pixels and `.hdr` files: three 8 bit mantissas sharing one
+//! exponent byte, so every pixel keeps its full dynamic range in 4 bytes.
+
+use std::io::{self, BufRead, Write};
+
+use crate::vec3::Color3d;
+
+// the brightest channel an rgbe pixel holds, 255 / 256 * 2^127
+const RGBE_MAX: f64 = 255.0 * (1u128 << 119) as f64;
+
+pub fn to_rgbe(color: Color3d) -> [u8; 4] {
+    // saturate before anything is scaled, nan and negative channels are 0
+    let saturate = |c: f64| {
+        if c.is_nan() {
+            0.0
+        } else {
+            c.clamp(0.0, RGBE_MAX)
+        }
+    };
+    let color = Color3d::new(saturate(color.x), saturate(color.y), saturate(color.z));
+    let v = color.x.max(color.y).max(color.z);
+    if v < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    // frexp: v = m * 2^e with m in [0.5, 1)
+    let mut e = v.log2().floor() as i32 + 1;
+    let mut m = v / 2f64.powi(e);
+    if m >= 1.0 {
+        m /= 2.0;
+        e += 1;
+    }
+    let scale = m * 256.0 / v;
+
+    [
+        (color.x * scale) as u8,
+        (color.y * scale) as u8,
+        (color.z * scale) as u8,
+        (e + 128) as u8,
+    ]
+}
+
+pub fn from_rgbe(rgbe: [u8; 4]) -> Color3d {
+    if rgbe[3] == 0 {
+        return Color3d::zero();
+    }
+    let f = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+    Color3d::new(
+        (rgbe[0] as f64 + 0.5) * f,
+        (rgbe[1] as f64 + 0.5) * f,
+        (rgbe[2] as f64 + 0.5) * f,
+    )
+}
+
+/// Writes a flat (not run-length encoded) `.hdr` image, `pixels` row by row
+/// from the top.
+pub fn write_hdr(
+    fp: &mut impl Write,
+    width: usize,
+    height: usize,
+    pixels: impl Iterator<Item = Color3d>,
+) -> io::Result<()> {
+    write!(
+        fp,
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        height, width
+    )?;
+    let mut data = Vec::with_capacity(4 * width * height);
+    for color in pixels {
+        data.extend_from_slice(&to_rgbe(color));
+    }
+    fp.write_all(&data)
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Reads an `.hdr` image, flat or run-length encoded, and returns its size
+/// and its pixels row by row from the top.
+pub fn read_hdr(fp: &mut impl BufRead) -> io::Result<(usize, usize, Vec<Color3d>)> {
+    let mut line = String::new();
+    fp.read_line(&mut line)?;
+    if !line.starts_with("#?") {
+        return Err(invalid("not a radiance hdr file"));
+    }
+
+    // header lines up to an empty one, then the resolution
+    loop {
+        line.clear();
+        if fp.read_line(&mut line)? == 0 {
+            return Err(invalid("unexpected end of the hdr header"));
+        }
+        let l = line.trim();
+        if l.is_empty() {
+            break;
+        }
+        if let Some(format) = l.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(invalid(format!(
+                    "unsupported hdr pixel format '{}'",
+                    format
+                )));
+            }
+        }
+    }
+    line.clear();
+    fp.read_line(&mut line)?;
+    let (height, width) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", h, "+X", w] => match (h.parse::<usize>(), w.parse::<usize>()) {
+            (Ok(h), Ok(w)) if h > 0 && w > 0 => (h, w),
+            _ => return Err(invalid("invalid hdr image size")),
+        },
+        _ => {
+            return Err(invalid(format!(
+                "unsupported hdr orientation '{}', expected -Y H +X W",
+                line.trim()
+            )))
+        }
+    };
+
+    let mut pixels = Vec::with_capacity(width * height);
+    let mut scanline = vec![[0u8; 4]; width];
+    for _ in 0..height {
+        read_scanline(fp, &mut scanline)?;
+        pixels.extend(scanline.iter().map(|&rgbe| from_rgbe(rgbe)));
+    }
+    Ok((width, height, pixels))
+}
+
+fn read_scanline(fp: &mut impl BufRead, scanline: &mut [[u8; 4]]) -> io::Result<()> {
+    let width = scanline.len();
+    let mut first = [0u8; 4];
+    fp.read_exact(&mut first)?;
+
+    let rle = (8..0x8000).contains(&width)
+        && first[0] == 2
+        && first[1] == 2
+        && first[2] < 128
+        && ((first[2] as usize) << 8 | first[3] as usize) == width;
+    if !rle {
+        return read_flat_scanline(fp, first, scanline);
+    }
+
+    // new style rle: each of the four components is encoded on its own
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let mut count = [0u8; 1];
+            fp.read_exact(&mut count)?;
+            let (run, count) = if count[0] > 128 {
+                (true, count[0] as usize - 128)
+            } else {
+                (false, count[0] as usize)
+            };
+            if count == 0 || x + count > width {
+                return Err(invalid("corrupt hdr scanline"));
+            }
+            if run {
+                let mut value = [0u8; 1];
+                fp.read_exact(&mut value)?;
+                for pixel in &mut scanline[x..x + count] {
+                    pixel[channel] = value[0];
+                }
+            } else {
+                let mut values = [0u8; 128];
+                fp.read_exact(&mut values[..count])?;
+                for (pixel, &value) in scanline[x..x + count].iter_mut().zip(&values[..count]) {
+                    pixel[channel] = value;
+                }
+            }
+            x += count;
+        }
+    }
+    Ok(())
+}
+
+// flat pixels, where old style rle marks repeats of the previous pixel
+// with 1, 1, 1, count
+fn read_flat_scanline(
+    fp: &mut impl BufRead,
+    first: [u8; 4],
+    scanline: &mut [[u8; 4]],
+) -> io::Result<()> {
+    let mut pixel = first;
+    let mut x = 0;
+    let mut shift = 0;
+    loop {
+        if pixel[0] == 1 && pixel[1] == 1 && pixel[2] == 1 {
+            if x == 0 {
+                return Err(invalid("corrupt hdr scanline"));
+            }
+            let count = (pixel[3] as usize).checked_shl(shift).unwrap_or(usize::MAX);
+            if count > scanline.len() - x {
+                return Err(invalid("corrupt hdr scanline"));
+            }
+            let previous = scanline[x - 1];
+            scanline[x..x + count].fill(previous);
+            x += count;
+            shift += 8;
+        } else {
+            scanline[x] = pixel;
+            x += 1;
+            shift = 0;
+        }
+        if x == scanline.len() {
+            return Ok(());
+        }
+        fp.read_exact(&mut pixel)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_keeps_colors_within_a_mantissa_step() {
+        for &v in &[1e-30, 0.001, 0.5, 1.0, 3.75, 1000.0, 1e20, 1e38] {
+            let color = Color3d::new(v, v * 0.5, v * 0.01);
+            let decoded = from_rgbe(to_rgbe(color));
+            for (a, b) in [
+                (color.x, decoded.x),
+                (color.y, decoded.y),
+                (color.z, decoded.z),
+            ] {
+                assert!((a - b).abs() <= v / 128.0, "{} decoded as {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn encodes_exact_values() {
+        assert_eq!(to_rgbe(Color3d::new(1.0, 0.5, 0.0)), [128, 64, 0, 129]);
+        assert_eq!(to_rgbe(Color3d::zero()), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn saturates_huge_and_infinite_channels() {
+        let max = [255, 0, 0, 255];
+        assert_eq!(to_rgbe(Color3d::new(RGBE_MAX, 0.0, 0.0)), max);
+        assert_eq!(to_rgbe(Color3d::new(2f64.powi(127), 0.0, 0.0)), max);
+        assert_eq!(to_rgbe(Color3d::new(1e300, 0.0, 0.0)), max);
+        assert_eq!(to_rgbe(Color3d::new(f64::INFINITY, 0.0, 0.0)), max);
+        assert_eq!(
+            to_rgbe(Color3d::new(f64::INFINITY, f64::INFINITY, 1.0)),
+            [255, 255, 0, 255]
+        );
+    }
+
+    #[test]
+    fn drops_nan_and_negative_channels() {
+        assert_eq!(to_rgbe(Color3d::new(f64::NAN, 0.0, 0.0)), [0, 0, 0, 0]);
+        assert_eq!(to_rgbe(Color3d::new(-1.0, f64::NAN, 1.0)), [0, 0, 128, 129]);
+        assert_eq!(
+            to_rgbe(Color3d::new(f64::NEG_INFINITY, 0.0, 0.0)),
+            [0, 0, 0, 0]
+        );
+    }
+}
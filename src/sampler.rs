@@ -0,0 +1,38 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Source of every random number used while building and rendering a scene.
+/// Two samplers created with the same seed produce the same sequence on every
+/// platform, so a seed is enough to reproduce an image.
+#[derive(Clone)]
+pub struct Sampler {
+    rng: ChaCha8Rng,
+}
+
+impl Sampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn from_entropy() -> Self {
+        Self {
+            rng: ChaCha8Rng::from_entropy(),
+        }
+    }
+
+    pub fn random_double(&mut self) -> f64 {
+        // [0, 1)
+        self.rng.gen::<f64>()
+    }
+
+    pub fn random_range(&mut self, min: f64, max: f64) -> f64 {
+        // [min, max)
+        if max - min <= f64::EPSILON {
+            min
+        } else {
+            self.rng.gen_range(min..max)
+        }
+    }
+}
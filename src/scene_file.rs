@@ -0,0 +1,1205 @@
+//! Loader for scenes described in toml, for example:
+//!
+//! ```toml
+//! [image]
+//! aspect_ratio = 1.5
+//! width = 600
+//!
+//! [camera]
+//! lookfrom = [13, 2, 3]
+//! lookat = [0, 0, 0]
+//! vfov = 20
+//! aperture = 0.1
+//! focus_dist = 10
+//!
+//! [materials.ground]
+//! type = "diffuse"
+//! albedo = [0.5, 0.5, 0.5]
+//!
+//! [materials.glass]
+//! type = "dielectric"
+//! ir = 1.5
+//!
+//! [[objects]]
+//! type = "sphere"
+//! center = [0, -1000, 0]
+//! radius = 1000
+//! material = "ground"
+//! ```
+//!
+//! Materials are `diffuse` (`albedo`), `metal` (`albedo`, `fuzz`),
+//! `dielectric` (`ir`), `light` (`emit`) and `isotropic` (`albedo`), which
+//! scatters in every direction. An `albedo` or `emit` is a
+//! color or the name of an entry in the `[textures]` table: `solid`
+//! (`color`), the solid `checker` (cubes of size `scale` in `even` and `odd`
+//! colors), `uv_checker` (`columns` by `rows` squares over the surface) or
+//! `image` (`path` to an `.hdr`, `.exr`, jpeg or png relative to the scene
+//! file). Images are filtered `trilinear` by default, or `bilinear` or
+//! `nearest`, and `wrap` as `repeat` (the default), `clamp` or `mirror`
+//! outside the texture coordinates from 0 to 1. Each file is loaded once
+//! however many textures use it. The procedural `noise`, `turbulence`,
+//! `marble`, `wood` and `worley` textures take a `seed` (0 by default), a
+//! `scale` (1), the `depth` of turbulence in octaves (7) and colors `low`
+//! and `high` to blend between.
+//! Objects are `sphere` (`center`,
+//! `radius`), `quad` (corner `q` and edges `u`, `v`), the infinite `plane`
+//! (`point`, `normal`), axis aligned `box` (`min`, `max`) and `triangle`
+//! (corners `a`, `b`, `c`, counterclockwise seen from the front). `mesh`
+//! loads the `.obj`, `.ply`, `.stl`, `.gltf` or `.glb` file at `path`.
+//! Wavefront files bring the materials of their `.mtl` and glTF files their
+//! own unless a `material` is given, the others need one. The cameras of
+//! glTF files are ignored here. `smooth = true` shades meshes that come
+//! without normals smoothly.
+//!
+//! A sphere or box with a `density` is filled with fog or smoke that
+//! scatters by its material, usually `isotropic`, instead of being solid.
+//!
+//! Any object can be scaled by `scale` (a number or one per axis), turned by
+//! `rotate` (degrees about x, then y, then z) and moved by `translate`, in
+//! that order. An object with `motion` moves that far from time 0 to time 1.
+//!
+//! The camera takes the arguments of [`Camera::new`], `vup` defaults to
+//! `[0, 1, 0]`, `aperture` to 0 and `focus_dist` to the distance between
+//! `lookfrom` and `lookat`. `shutter = [open, close]` blurs moving objects
+//! over that time, by default the shutter opens and closes at 0.
+//!
+//! An optional `[environment]` table sets what rays leaving the scene see:
+//! `solid` (`color`, `[0, 0, 0]` leaves only the lights), `gradient`
+//! (`bottom`, `top`, the sky of the book by default), `image` (`path` to a
+//! latitude-longitude `.hdr`, `.exr` or png relative to the scene file,
+//! `rotation` in degrees and `intensity`) or the physical `sky` (`elevation`
+//! and `azimuth` of the sun in degrees, `turbidity`, `ground_albedo` and
+//! `intensity`).
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{
+    camera::Camera,
+    constant_medium::ConstantMedium,
+    cuboid::{self, Cuboid},
+    environment::{Environment, EnvironmentMap, Gradient, SolidColor},
+    gltf_scene::GltfScene,
+    hittable::Hittable,
+    hittable_list::HittableList,
+    linear_bvh::LinearBvh,
+    material::{Dieletric, Diffuse, DiffuseLight, Isotropic, Material, Metal},
+    matrix::Matrix,
+    moving_sphere::MovingSphere,
+    obj::ObjFile,
+    plane::Plane,
+    ply::load_ply,
+    quad::{Quad, QuadShape},
+    scenes::SceneSettings,
+    sky::PhysicalSky,
+    sphere::Sphere,
+    stl::load_stl,
+    texture::{
+        CheckerTexture, ImageFilter, ImageTexture, MarbleTexture, NoiseTexture, SolidTexture,
+        Texture, TurbulenceTexture, UvCheckerTexture, WoodTexture, WorleyTexture, WrapMode,
+        DEFAULT_DEPTH,
+    },
+    transformed::{Animated, Transformed},
+    triangle::Triangle,
+    vec3::{Point3d, Vec3d},
+};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    /// `line` and `column` start at 1.
+    Invalid {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(e) => write!(f, "{}", e),
+            SceneError::Invalid {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(e: io::Error) -> Self {
+        SceneError::Io(e)
+    }
+}
+
+/// Camera arguments as written in the file, the aspect ratio is only known
+/// once the image size is settled.
+#[derive(Debug, Clone, Copy)]
+pub struct CameraSettings {
+    pub lookfrom: Point3d,
+    pub lookat: Point3d,
+    pub vup: Vec3d,
+    pub vfov: f64,
+    pub aperture: f64,
+    pub focus_dist: f64,
+    /// when the shutter opens and closes
+    pub time0: f64,
+    pub time1: f64,
+}
+
+impl CameraSettings {
+    pub fn build(&self, aspect_ratio: f64) -> Camera {
+        Camera::new(
+            self.lookfrom,
+            self.lookat,
+            self.vup,
+            self.vfov,
+            aspect_ratio,
+            self.aperture,
+            self.focus_dist,
+        )
+        .with_shutter(self.time0, self.time1)
+    }
+}
+
+pub struct LoadedScene {
+    pub settings: SceneSettings,
+    pub camera: CameraSettings,
+    pub world: HittableList,
+    pub environment: Box<dyn Environment>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    #[serde(default)]
+    image: ImageDesc,
+    camera: CameraDesc,
+    environment: Option<EnvironmentDesc>,
+    #[serde(default)]
+    textures: BTreeMap<String, TextureDesc>,
+    #[serde(default)]
+    materials: BTreeMap<String, MaterialDesc>,
+    #[serde(default)]
+    objects: Vec<ObjectDesc>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ImageDesc {
+    aspect_ratio: Option<Spanned<f64>>,
+    width: Option<Spanned<i64>>,
+    samples_per_pixel: Option<Spanned<i64>>,
+    max_depth: Option<Spanned<i64>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDesc {
+    lookfrom: Spanned<[f64; 3]>,
+    lookat: Spanned<[f64; 3]>,
+    vup: Option<Spanned<[f64; 3]>>,
+    vfov: Spanned<f64>,
+    aperture: Option<Spanned<f64>>,
+    focus_dist: Option<Spanned<f64>>,
+    shutter: Option<Spanned<[f64; 2]>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnvironmentDesc {
+    #[serde(rename = "type")]
+    kind: Spanned<String>,
+    color: Option<Spanned<[f64; 3]>>,
+    bottom: Option<Spanned<[f64; 3]>>,
+    top: Option<Spanned<[f64; 3]>>,
+    path: Option<Spanned<String>>,
+    rotation: Option<Spanned<f64>>,
+    intensity: Option<Spanned<f64>>,
+    elevation: Option<Spanned<f64>>,
+    azimuth: Option<Spanned<f64>>,
+    turbidity: Option<Spanned<f64>>,
+    ground_albedo: Option<Spanned<[f64; 3]>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TextureDesc {
+    #[serde(rename = "type")]
+    kind: Spanned<String>,
+    color: Option<Spanned<[f64; 3]>>,
+    scale: Option<Spanned<f64>>,
+    columns: Option<Spanned<f64>>,
+    rows: Option<Spanned<f64>>,
+    even: Option<Spanned<[f64; 3]>>,
+    odd: Option<Spanned<[f64; 3]>>,
+    path: Option<Spanned<String>>,
+    filter: Option<Spanned<String>>,
+    wrap: Option<Spanned<String>>,
+    seed: Option<Spanned<i64>>,
+    depth: Option<Spanned<i64>>,
+    low: Option<Spanned<[f64; 3]>>,
+    high: Option<Spanned<[f64; 3]>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialDesc {
+    #[serde(rename = "type")]
+    kind: Spanned<String>,
+    albedo: Option<Spanned<ColorOrTexture>>,
+    fuzz: Option<Spanned<f64>>,
+    ir: Option<Spanned<f64>>,
+    emit: Option<Spanned<ColorOrTexture>>,
+}
+
+/// A constant color or the name of a texture.
+#[derive(Deserialize)]
+#[serde(untagged, expecting = "expected a color or the name of a texture")]
+enum ColorOrTexture {
+    Color([f64; 3]),
+    Texture(String),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjectDesc {
+    #[serde(rename = "type")]
+    kind: Spanned<String>,
+    center: Option<Spanned<[f64; 3]>>,
+    radius: Option<Spanned<f64>>,
+    q: Option<Spanned<[f64; 3]>>,
+    u: Option<Spanned<[f64; 3]>>,
+    v: Option<Spanned<[f64; 3]>>,
+    point: Option<Spanned<[f64; 3]>>,
+    normal: Option<Spanned<[f64; 3]>>,
+    min: Option<Spanned<[f64; 3]>>,
+    max: Option<Spanned<[f64; 3]>>,
+    a: Option<Spanned<[f64; 3]>>,
+    b: Option<Spanned<[f64; 3]>>,
+    c: Option<Spanned<[f64; 3]>>,
+    path: Option<Spanned<String>>,
+    scale: Option<Spanned<Scale>>,
+    rotate: Option<Spanned<[f64; 3]>>,
+    translate: Option<Spanned<[f64; 3]>>,
+    motion: Option<Spanned<[f64; 3]>>,
+    smooth: Option<Spanned<bool>>,
+    density: Option<Spanned<f64>>,
+    material: Option<Spanned<String>>,
+}
+
+/// One factor for every axis or one per axis.
+#[derive(Deserialize)]
+#[serde(untagged, expecting = "a number or an array of three numbers")]
+enum Scale {
+    Uniform(f64),
+    PerAxis([f64; 3]),
+}
+
+/// `object` placed by `placement` at time 0 and moved on by `motion` until
+/// time 1.
+fn moving(
+    object: impl Hittable + 'static,
+    placement: Option<Matrix>,
+    motion: Vec3d,
+) -> Box<dyn Hittable> {
+    let start = placement.unwrap_or_default();
+    let end = Matrix::translation(motion) * start;
+    Box::new(Animated::new(object, start, end, 0.0, 1.0))
+}
+
+/// Turns byte offsets into the line and column numbers of `SceneError`.
+struct Source<'a> {
+    text: &'a str,
+    /// files named in the scene are relative to this directory
+    dir: &'a Path,
+}
+
+impl<'a> Source<'a> {
+    fn error(&self, span: Range<usize>, message: impl Into<String>) -> SceneError {
+        let before = &self.text[..span.start.min(self.text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        SceneError::Invalid {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn vec3(&self, value: &Spanned<[f64; 3]>, name: &str) -> Result<Vec3d, SceneError> {
+        let [x, y, z] = *value.get_ref();
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            return Err(self.error(value.span(), format!("`{}` must be finite", name)));
+        }
+        Ok(Vec3d::new(x, y, z))
+    }
+
+    fn color(&self, value: &Spanned<[f64; 3]>, name: &str) -> Result<Vec3d, SceneError> {
+        let c = self.vec3(value, name)?;
+        if c.x < 0.0 || c.y < 0.0 || c.z < 0.0 {
+            return Err(self.error(value.span(), format!("`{}` must not be negative", name)));
+        }
+        Ok(c)
+    }
+
+    fn positive(&self, value: &Spanned<f64>, name: &str) -> Result<f64, SceneError> {
+        let v = *value.get_ref();
+        if !(v.is_finite() && v > 0.0) {
+            return Err(self.error(value.span(), format!("`{}` must be greater than 0", name)));
+        }
+        Ok(v)
+    }
+
+    fn positive_int(&self, value: &Spanned<i64>, name: &str) -> Result<i64, SceneError> {
+        let v = *value.get_ref();
+        if v <= 0 || v > i32::MAX as i64 {
+            return Err(self.error(value.span(), format!("`{}` must be greater than 0", name)));
+        }
+        Ok(v)
+    }
+
+    fn settings(&self, desc: &ImageDesc) -> Result<SceneSettings, SceneError> {
+        let mut settings = SceneSettings::default();
+        if let Some(aspect_ratio) = &desc.aspect_ratio {
+            settings.aspect_ratio = self.positive(aspect_ratio, "aspect_ratio")?;
+        }
+        if let Some(width) = &desc.width {
+            settings.image_width = self.positive_int(width, "width")? as usize;
+        }
+        if let Some(spp) = &desc.samples_per_pixel {
+            settings.samples_per_pixel = self.positive_int(spp, "samples_per_pixel")? as i32;
+        }
+        if let Some(max_depth) = &desc.max_depth {
+            settings.max_depth = self.positive_int(max_depth, "max_depth")? as i32;
+        }
+        Ok(settings)
+    }
+
+    fn camera(&self, desc: &CameraDesc) -> Result<CameraSettings, SceneError> {
+        let lookfrom = self.vec3(&desc.lookfrom, "lookfrom")?;
+        let lookat = self.vec3(&desc.lookat, "lookat")?;
+        let distance = (lookfrom - lookat).length();
+        if distance <= f64::EPSILON {
+            return Err(self.error(desc.lookat.span(), "`lookat` must differ from `lookfrom`"));
+        }
+
+        let vup = match &desc.vup {
+            Some(vup) => {
+                let v = self.vec3(vup, "vup")?;
+                if v.cross(&(lookfrom - lookat)).near_zero() {
+                    return Err(self.error(
+                        vup.span(),
+                        "`vup` must not be parallel to the viewing direction",
+                    ));
+                }
+                v
+            }
+            None => Vec3d::new(0.0, 1.0, 0.0),
+        };
+
+        let vfov = *desc.vfov.get_ref();
+        if !(vfov > 0.0 && vfov < 180.0) {
+            return Err(self.error(desc.vfov.span(), "`vfov` must be between 0 and 180 degrees"));
+        }
+
+        let aperture = match &desc.aperture {
+            Some(aperture) if *aperture.get_ref() < 0.0 || !aperture.get_ref().is_finite() => {
+                return Err(self.error(aperture.span(), "`aperture` must not be negative"))
+            }
+            Some(aperture) => *aperture.get_ref(),
+            None => 0.0,
+        };
+        let focus_dist = match &desc.focus_dist {
+            Some(focus_dist) => self.positive(focus_dist, "focus_dist")?,
+            None => distance,
+        };
+        let [time0, time1] = match &desc.shutter {
+            Some(shutter) => {
+                let [open, close] = *shutter.get_ref();
+                if !(open.is_finite() && close.is_finite() && open <= close) {
+                    return Err(self.error(
+                        shutter.span(),
+                        "`shutter` must be finite and open before it closes",
+                    ));
+                }
+                [open, close]
+            }
+            None => [0.0, 0.0],
+        };
+
+        Ok(CameraSettings {
+            lookfrom,
+            lookat,
+            vup,
+            vfov,
+            aperture,
+            focus_dist,
+            time0,
+            time1,
+        })
+    }
+
+    fn environment(&self, desc: &EnvironmentDesc) -> Result<Box<dyn Environment>, SceneError> {
+        let kind = desc.kind.get_ref().as_str();
+        let fields = [
+            ("color", desc.color.as_ref().map(Spanned::span)),
+            ("bottom", desc.bottom.as_ref().map(Spanned::span)),
+            ("top", desc.top.as_ref().map(Spanned::span)),
+            ("path", desc.path.as_ref().map(Spanned::span)),
+            ("rotation", desc.rotation.as_ref().map(Spanned::span)),
+            ("intensity", desc.intensity.as_ref().map(Spanned::span)),
+            ("elevation", desc.elevation.as_ref().map(Spanned::span)),
+            ("azimuth", desc.azimuth.as_ref().map(Spanned::span)),
+            ("turbidity", desc.turbidity.as_ref().map(Spanned::span)),
+            (
+                "ground_albedo",
+                desc.ground_albedo.as_ref().map(Spanned::span),
+            ),
+        ];
+        let allowed: &[&str] = match kind {
+            "solid" => &["color"],
+            "gradient" => &["bottom", "top"],
+            "image" => &["path", "rotation", "intensity"],
+            "sky" => &[
+                "elevation",
+                "azimuth",
+                "turbidity",
+                "ground_albedo",
+                "intensity",
+            ],
+            other => {
+                return Err(self.error(
+                    desc.kind.span(),
+                    format!(
+                        "unknown environment type '{}', expected solid, gradient, image or sky",
+                        other
+                    ),
+                ))
+            }
+        };
+        for (name, span) in fields {
+            if let Some(span) = span.filter(|_| !allowed.contains(&name)) {
+                return Err(self.error(
+                    span,
+                    format!("`{}` is not a property of {} environments", name, kind),
+                ));
+            }
+        }
+
+        match kind {
+            "solid" => {
+                let color = match &desc.color {
+                    Some(color) => self.color(color, "color")?,
+                    None => {
+                        return Err(
+                            self.error(desc.kind.span(), "solid environment is missing `color`")
+                        )
+                    }
+                };
+                Ok(Box::new(SolidColor::new(color)))
+            }
+            "gradient" => {
+                let mut gradient = Gradient::sky();
+                if let Some(bottom) = &desc.bottom {
+                    gradient.bottom = self.color(bottom, "bottom")?;
+                }
+                if let Some(top) = &desc.top {
+                    gradient.top = self.color(top, "top")?;
+                }
+                Ok(Box::new(gradient))
+            }
+            "sky" => {
+                let angle = |value: &Option<Spanned<f64>>, name: &str, default: f64| match value {
+                    Some(value) if !value.get_ref().is_finite() => {
+                        Err(self.error(value.span(), format!("`{}` must be finite", name)))
+                    }
+                    Some(value) => Ok(*value.get_ref()),
+                    None => Ok(default),
+                };
+                let elevation = angle(&desc.elevation, "elevation", 45.0)?;
+                if !(-90.0..=90.0).contains(&elevation) {
+                    let span = desc.elevation.as_ref().map_or(0..0, Spanned::span);
+                    return Err(self.error(span, "`elevation` must be between -90 and 90 degrees"));
+                }
+                let mut sky = PhysicalSky::new(elevation, angle(&desc.azimuth, "azimuth", 0.0)?);
+                if let Some(turbidity) = &desc.turbidity {
+                    let t = *turbidity.get_ref();
+                    if !(1.7..=10.0).contains(&t) {
+                        return Err(
+                            self.error(turbidity.span(), "`turbidity` must be between 1.7 and 10")
+                        );
+                    }
+                    sky = sky.with_turbidity(t);
+                }
+                if let Some(albedo) = &desc.ground_albedo {
+                    sky = sky.with_ground_albedo(self.color(albedo, "ground_albedo")?);
+                }
+                if let Some(intensity) = &desc.intensity {
+                    sky = sky.with_intensity(self.positive(intensity, "intensity")?);
+                }
+                Ok(Box::new(sky))
+            }
+            _ => {
+                let path = desc.path.as_ref().ok_or_else(|| {
+                    self.error(desc.kind.span(), "image environment is missing `path`")
+                })?;
+                let file = self.dir.join(path.get_ref());
+                let mut map = EnvironmentMap::load(&file).map_err(|e| {
+                    self.error(
+                        path.span(),
+                        format!("cannot load '{}': {}", file.display(), e),
+                    )
+                })?;
+                if let Some(rotation) = &desc.rotation {
+                    if !rotation.get_ref().is_finite() {
+                        return Err(self.error(rotation.span(), "`rotation` must be finite"));
+                    }
+                    map = map.with_rotation(*rotation.get_ref());
+                }
+                if let Some(intensity) = &desc.intensity {
+                    map = map.with_intensity(self.positive(intensity, "intensity")?);
+                }
+                Ok(Box::new(map))
+            }
+        }
+    }
+
+    fn texture(&self, desc: &TextureDesc) -> Result<Arc<dyn Texture>, SceneError> {
+        let kind = desc.kind.get_ref().as_str();
+        let fields = [
+            ("color", desc.color.as_ref().map(Spanned::span)),
+            ("scale", desc.scale.as_ref().map(Spanned::span)),
+            ("columns", desc.columns.as_ref().map(Spanned::span)),
+            ("rows", desc.rows.as_ref().map(Spanned::span)),
+            ("even", desc.even.as_ref().map(Spanned::span)),
+            ("odd", desc.odd.as_ref().map(Spanned::span)),
+            ("path", desc.path.as_ref().map(Spanned::span)),
+            ("filter", desc.filter.as_ref().map(Spanned::span)),
+            ("wrap", desc.wrap.as_ref().map(Spanned::span)),
+            ("seed", desc.seed.as_ref().map(Spanned::span)),
+            ("depth", desc.depth.as_ref().map(Spanned::span)),
+            ("low", desc.low.as_ref().map(Spanned::span)),
+            ("high", desc.high.as_ref().map(Spanned::span)),
+        ];
+        let allowed: &[&str] = match kind {
+            "solid" => &["color"],
+            "checker" => &["scale", "even", "odd"],
+            "uv_checker" => &["columns", "rows", "even", "odd"],
+            "image" => &["path", "filter", "wrap"],
+            "noise" | "worley" => &["seed", "scale", "low", "high"],
+            "turbulence" | "marble" | "wood" => &["seed", "scale", "depth", "low", "high"],
+            other => {
+                return Err(self.error(
+                    desc.kind.span(),
+                    format!(
+                        "unknown texture type '{}', expected solid, checker, uv_checker, image, \
+                         noise, turbulence, marble, wood or worley",
+                        other
+                    ),
+                ))
+            }
+        };
+        for (name, span) in fields {
+            if let Some(span) = span.filter(|_| !allowed.contains(&name)) {
+                return Err(self.error(
+                    span,
+                    format!("`{}` is not a property of {} textures", name, kind),
+                ));
+            }
+        }
+        let missing = |name: &str| {
+            self.error(
+                desc.kind.span(),
+                format!("{} texture is missing `{}`", kind, name),
+            )
+        };
+        let color = |value: &Option<Spanned<[f64; 3]>>, name: &str| match value {
+            Some(value) => self.color(value, name),
+            None => Err(missing(name)),
+        };
+        let positive = |value: &Option<Spanned<f64>>, name: &str| match value {
+            Some(value) => self.positive(value, name),
+            None => Err(missing(name)),
+        };
+
+        // every procedural texture has its own default colors
+        let colors = match (&desc.low, &desc.high) {
+            (None, None) => None,
+            (low, high) => Some((color(low, "low")?, color(high, "high")?)),
+        };
+        let seed = match &desc.seed {
+            Some(seed) if *seed.get_ref() < 0 => {
+                return Err(self.error(seed.span(), "`seed` must not be negative"))
+            }
+            Some(seed) => *seed.get_ref() as u64,
+            None => 0,
+        };
+        let scale = match &desc.scale {
+            Some(scale) => self.positive(scale, "scale")?,
+            None => 1.0,
+        };
+        let depth = match &desc.depth {
+            Some(depth) => self.positive_int(depth, "depth")? as usize,
+            None => DEFAULT_DEPTH,
+        };
+
+        Ok(match kind {
+            "noise" => {
+                let mut texture = NoiseTexture::new(seed, scale);
+                if let Some((low, high)) = colors {
+                    texture = texture.with_colors(low, high);
+                }
+                Arc::new(texture)
+            }
+            "turbulence" => {
+                let mut texture = TurbulenceTexture::new(seed, scale).with_depth(depth);
+                if let Some((low, high)) = colors {
+                    texture = texture.with_colors(low, high);
+                }
+                Arc::new(texture)
+            }
+            "marble" => {
+                let mut texture = MarbleTexture::new(seed, scale).with_depth(depth);
+                if let Some((low, high)) = colors {
+                    texture = texture.with_colors(low, high);
+                }
+                Arc::new(texture)
+            }
+            "wood" => {
+                let mut texture = WoodTexture::new(seed, scale).with_depth(depth);
+                if let Some((low, high)) = colors {
+                    texture = texture.with_colors(low, high);
+                }
+                Arc::new(texture)
+            }
+            "worley" => {
+                let mut texture = WorleyTexture::new(seed, scale);
+                if let Some((low, high)) = colors {
+                    texture = texture.with_colors(low, high);
+                }
+                Arc::new(texture)
+            }
+            "solid" => Arc::new(SolidTexture::new(color(&desc.color, "color")?)),
+            "checker" => Arc::new(CheckerTexture::from_colors(
+                positive(&desc.scale, "scale")?,
+                color(&desc.even, "even")?,
+                color(&desc.odd, "odd")?,
+            )),
+            "uv_checker" => Arc::new(UvCheckerTexture::from_colors(
+                positive(&desc.columns, "columns")?,
+                positive(&desc.rows, "rows")?,
+                color(&desc.even, "even")?,
+                color(&desc.odd, "odd")?,
+            )),
+            _ => {
+                let filter = match desc.filter.as_ref() {
+                    None => ImageFilter::Trilinear,
+                    Some(filter) => match filter.get_ref().as_str() {
+                        "nearest" => ImageFilter::Nearest,
+                        "bilinear" => ImageFilter::Bilinear,
+                        "trilinear" => ImageFilter::Trilinear,
+                        other => {
+                            return Err(self.error(
+                                filter.span(),
+                                format!(
+                                    "unknown filter '{}', expected nearest, bilinear or trilinear",
+                                    other
+                                ),
+                            ))
+                        }
+                    },
+                };
+                let wrap = match desc.wrap.as_ref() {
+                    None => WrapMode::Repeat,
+                    Some(wrap) => match wrap.get_ref().as_str() {
+                        "repeat" => WrapMode::Repeat,
+                        "clamp" => WrapMode::Clamp,
+                        "mirror" => WrapMode::Mirror,
+                        other => {
+                            return Err(self.error(
+                                wrap.span(),
+                                format!(
+                                    "unknown wrap mode '{}', expected repeat, clamp or mirror",
+                                    other
+                                ),
+                            ))
+                        }
+                    },
+                };
+                let path = desc.path.as_ref().ok_or_else(|| missing("path"))?;
+                let file = self.dir.join(path.get_ref());
+                let texture = ImageTexture::load(&file).map_err(|e| {
+                    self.error(
+                        path.span(),
+                        format!("cannot load '{}': {}", file.display(), e),
+                    )
+                })?;
+                Arc::new(texture.with_filter(filter).with_wrap(wrap))
+            }
+        })
+    }
+
+    /// The texture of a color, or the one named.
+    fn color_or_texture(
+        &self,
+        value: &Spanned<ColorOrTexture>,
+        name: &str,
+        textures: &BTreeMap<String, Arc<dyn Texture>>,
+    ) -> Result<Arc<dyn Texture>, SceneError> {
+        match value.get_ref() {
+            ColorOrTexture::Color(color) => {
+                let color = self.color(&Spanned::new(value.span(), *color), name)?;
+                Ok(Arc::new(SolidTexture::new(color)))
+            }
+            ColorOrTexture::Texture(texture) => textures
+                .get(texture)
+                .cloned()
+                .ok_or_else(|| self.error(value.span(), format!("unknown texture '{}'", texture))),
+        }
+    }
+
+    fn material(
+        &self,
+        desc: &MaterialDesc,
+        textures: &BTreeMap<String, Arc<dyn Texture>>,
+    ) -> Result<Arc<dyn Material>, SceneError> {
+        let unexpected = |span: Option<Range<usize>>, name: &str| match span {
+            Some(span) => Err(self.error(
+                span,
+                format!(
+                    "`{}` is not a property of {} materials",
+                    name,
+                    desc.kind.get_ref()
+                ),
+            )),
+            None => Ok(()),
+        };
+        let fuzz = desc.fuzz.as_ref().map(Spanned::span);
+        let ir = desc.ir.as_ref().map(Spanned::span);
+        let emit = desc.emit.as_ref().map(Spanned::span);
+        let albedo = |desc: &MaterialDesc| match &desc.albedo {
+            Some(albedo) => self.color_or_texture(albedo, "albedo", textures),
+            None => Err(self.error(
+                desc.kind.span(),
+                format!("{} material is missing `albedo`", desc.kind.get_ref()),
+            )),
+        };
+
+        match desc.kind.get_ref().as_str() {
+            "diffuse" => {
+                unexpected(fuzz, "fuzz")?;
+                unexpected(ir, "ir")?;
+                unexpected(emit, "emit")?;
+                Ok(Arc::new(Diffuse::textured(albedo(desc)?)))
+            }
+            "metal" => {
+                unexpected(ir, "ir")?;
+                unexpected(emit, "emit")?;
+                let fuzz = match &desc.fuzz {
+                    Some(fuzz) if !(0.0..=1.0).contains(fuzz.get_ref()) => {
+                        return Err(self.error(fuzz.span(), "`fuzz` must be between 0 and 1"))
+                    }
+                    Some(fuzz) => *fuzz.get_ref(),
+                    None => 0.0,
+                };
+                Ok(Arc::new(Metal::textured(albedo(desc)?, fuzz)))
+            }
+            "dielectric" => {
+                unexpected(desc.albedo.as_ref().map(Spanned::span), "albedo")?;
+                unexpected(fuzz, "fuzz")?;
+                unexpected(emit, "emit")?;
+                let ir = match &desc.ir {
+                    Some(ir) => self.positive(ir, "ir")?,
+                    None => {
+                        return Err(
+                            self.error(desc.kind.span(), "dielectric material is missing `ir`")
+                        )
+                    }
+                };
+                Ok(Arc::new(Dieletric::new(ir)))
+            }
+            "isotropic" => {
+                unexpected(fuzz, "fuzz")?;
+                unexpected(ir, "ir")?;
+                unexpected(emit, "emit")?;
+                Ok(Arc::new(Isotropic::textured(albedo(desc)?)))
+            }
+            "light" => {
+                unexpected(desc.albedo.as_ref().map(Spanned::span), "albedo")?;
+                unexpected(fuzz, "fuzz")?;
+                unexpected(ir, "ir")?;
+                let emit = match &desc.emit {
+                    Some(emit) => self.color_or_texture(emit, "emit", textures)?,
+                    None => {
+                        return Err(self.error(desc.kind.span(), "light material is missing `emit`"))
+                    }
+                };
+                Ok(Arc::new(DiffuseLight::textured(emit)))
+            }
+            other => Err(self.error(
+                desc.kind.span(),
+                format!(
+                    "unknown material type '{}', expected diffuse, metal, dielectric, light or \
+                     isotropic",
+                    other
+                ),
+            )),
+        }
+    }
+
+    fn object(
+        &self,
+        desc: &ObjectDesc,
+        materials: &BTreeMap<String, Arc<dyn Material>>,
+        world: &mut HittableList,
+    ) -> Result<(), SceneError> {
+        let material = match &desc.material {
+            Some(name) => Some(materials.get(name.get_ref()).ok_or_else(|| {
+                self.error(
+                    name.span(),
+                    format!("unknown material '{}'", name.get_ref()),
+                )
+            })?),
+            None => None,
+        };
+
+        let kind = desc.kind.get_ref().as_str();
+        let vectors = [
+            ("center", &desc.center),
+            ("q", &desc.q),
+            ("u", &desc.u),
+            ("v", &desc.v),
+            ("point", &desc.point),
+            ("normal", &desc.normal),
+            ("min", &desc.min),
+            ("max", &desc.max),
+            ("a", &desc.a),
+            ("b", &desc.b),
+            ("c", &desc.c),
+            ("rotate", &desc.rotate),
+            ("translate", &desc.translate),
+            ("motion", &desc.motion),
+        ];
+        let allowed: &[&str] = match kind {
+            "sphere" => &["center", "radius", "density"],
+            "quad" => &["q", "u", "v"],
+            "plane" => &["point", "normal"],
+            "box" => &["min", "max", "density"],
+            "triangle" => &["a", "b", "c"],
+            "mesh" => &["path", "smooth"],
+            other => {
+                return Err(self.error(
+                    desc.kind.span(),
+                    format!(
+                    "unknown object type '{}', expected sphere, quad, plane, box, triangle or mesh",
+                    other
+                ),
+                ))
+            }
+        };
+        let radius = desc.radius.as_ref().map(|r| ("radius", r.span()));
+        let path = desc.path.as_ref().map(|p| ("path", p.span()));
+        let scale = desc.scale.as_ref().map(|s| ("scale", s.span()));
+        let smooth = desc.smooth.as_ref().map(|s| ("smooth", s.span()));
+        let density = desc.density.as_ref().map(|d| ("density", d.span()));
+        let present = vectors
+            .iter()
+            .filter_map(|(name, value)| value.as_ref().map(|v| (*name, v.span())))
+            .chain(radius)
+            .chain(path)
+            .chain(scale)
+            .chain(smooth)
+            .chain(density);
+        for (name, span) in present {
+            // every object can be placed and moved
+            if !allowed.contains(&name)
+                && !matches!(name, "scale" | "rotate" | "translate" | "motion")
+            {
+                return Err(self.error(
+                    span,
+                    format!("`{}` is not a property of {} objects", name, kind),
+                ));
+            }
+        }
+
+        let vec3 = |name: &str| -> Result<Vec3d, SceneError> {
+            let value = vectors
+                .iter()
+                .find(|(n, _)| *n == name)
+                .and_then(|(_, value)| value.as_ref())
+                .ok_or_else(|| {
+                    self.error(desc.kind.span(), format!("{} is missing `{}`", kind, name))
+                })?;
+            self.vec3(value, name)
+        };
+
+        let placement = self.placement(desc)?;
+        let motion = match &desc.motion {
+            Some(motion) => Some(self.vec3(motion, "motion")?),
+            None => None,
+        };
+        if kind == "mesh" {
+            if let Some(motion) = motion {
+                // the meshes are moved together, in place
+                let mut meshes = HittableList::new();
+                self.mesh(desc, material.cloned(), placement, &mut meshes)?;
+                world.push(moving(LinearBvh::new(meshes), None, motion));
+                return Ok(());
+            }
+            return self.mesh(desc, material.cloned(), placement, world);
+        }
+        let material = material
+            .ok_or_else(|| self.error(desc.kind.span(), format!("{} is missing `material`", kind)))?
+            .clone();
+        // a medium scatters by the material, the boundary does not use it
+        let density = match &desc.density {
+            Some(density) => Some(self.positive(density, "density")?),
+            None => None,
+        };
+        let phase = material.clone();
+        let fill = |object: Box<dyn Hittable>| -> Box<dyn Hittable> {
+            match density {
+                Some(density) => Box::new(ConstantMedium::new(object, density, phase.clone())),
+                None => object,
+            }
+        };
+        let object: Box<dyn Hittable> = match kind {
+            "sphere" => {
+                let center = vec3("center")?;
+                let radius = desc
+                    .radius
+                    .as_ref()
+                    .ok_or_else(|| self.error(desc.kind.span(), "sphere is missing `radius`"))?;
+                // negative radii are allowed, they flip the normals of hollow glass
+                if *radius.get_ref() == 0.0 || !radius.get_ref().is_finite() {
+                    return Err(self.error(radius.span(), "`radius` must not be 0"));
+                }
+                match motion.filter(|_| placement.is_none()) {
+                    Some(motion) => {
+                        world.push(fill(Box::new(MovingSphere::new(
+                            center,
+                            center + motion,
+                            0.0,
+                            1.0,
+                            *radius.get_ref(),
+                            material,
+                        ))));
+                        return Ok(());
+                    }
+                    None => Box::new(Sphere::new(center, *radius.get_ref(), material)),
+                }
+            }
+            "quad" => {
+                let (q, u, v) = (vec3("q")?, vec3("u")?, vec3("v")?);
+                if !QuadShape::spans(&u, &v) {
+                    return Err(self.error(
+                        desc.kind.span(),
+                        "`u` and `v` of a quad must not be zero or parallel",
+                    ));
+                }
+                Box::new(Quad::new(q, u, v, material))
+            }
+            "plane" => {
+                let point = vec3("point")?;
+                let normal = vec3("normal")?;
+                if normal.near_zero() {
+                    let span = desc.normal.as_ref().map_or(0..0, Spanned::span);
+                    return Err(self.error(span, "`normal` must not be zero"));
+                }
+                Box::new(Plane::new(point, normal, material))
+            }
+            "box" => {
+                let (min, max) = (vec3("min")?, vec3("max")?);
+                if !cuboid::encloses_volume(&min, &max) {
+                    return Err(self.error(
+                        desc.kind.span(),
+                        "`min` and `max` of a box must differ on every axis",
+                    ));
+                }
+                Box::new(Cuboid::new(min, max, material))
+            }
+            _ => {
+                let (a, b, c) = (vec3("a")?, vec3("b")?, vec3("c")?);
+                if (b - a).cross(&(c - a)).near_zero() {
+                    return Err(self.error(
+                        desc.kind.span(),
+                        "the corners of a triangle must not lie on a line",
+                    ));
+                }
+                Box::new(Triangle::new(a, b, c, material))
+            }
+        };
+        // media are filled in world space, so that placing them keeps the
+        // density as it is
+        let object = match (placement, motion) {
+            (placement, Some(motion)) => moving(object, placement, motion),
+            (Some(matrix), None) => Box::new(Transformed::new(object, matrix)),
+            (None, None) => object,
+        };
+        world.push(fill(object));
+        Ok(())
+    }
+
+    /// The matrix of `scale`, `rotate` and `translate`, if any is given.
+    fn placement(&self, desc: &ObjectDesc) -> Result<Option<Matrix>, SceneError> {
+        if desc.scale.is_none() && desc.rotate.is_none() && desc.translate.is_none() {
+            return Ok(None);
+        }
+        let mut scale = Vec3d::only(1.0);
+        if let Some(value) = &desc.scale {
+            scale = match value.get_ref() {
+                Scale::Uniform(s) => Vec3d::only(*s),
+                Scale::PerAxis([x, y, z]) => Vec3d::new(*x, *y, *z),
+            };
+            if !(scale.x * scale.y * scale.z).is_normal() {
+                return Err(self.error(value.span(), "`scale` must be finite and not 0"));
+            }
+        }
+        let rotation = match &desc.rotate {
+            Some(rotate) => self.vec3(rotate, "rotate")?,
+            None => Vec3d::zero(),
+        };
+        let translation = match &desc.translate {
+            Some(translate) => self.vec3(translate, "translate")?,
+            None => Vec3d::zero(),
+        };
+        Ok(Some(Matrix::placement(scale, rotation, translation)))
+    }
+
+    fn mesh(
+        &self,
+        desc: &ObjectDesc,
+        material: Option<Arc<dyn Material>>,
+        placement: Option<Matrix>,
+        world: &mut HittableList,
+    ) -> Result<(), SceneError> {
+        let path = desc
+            .path
+            .as_ref()
+            .ok_or_else(|| self.error(desc.kind.span(), "mesh is missing `path`"))?;
+        let smooth = desc.smooth.as_ref().is_some_and(|s| *s.get_ref());
+
+        let file = self.dir.join(path.get_ref());
+        let extension = file
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        // the errors of the loaders already name the file
+        let load_error = |e: io::Error| self.error(path.span(), e.to_string());
+        if extension.as_deref() == Some("obj") {
+            let mut obj = ObjFile::load(&file).map_err(load_error)?;
+            if let Some(material) = material {
+                obj = obj.with_material(material);
+            }
+            if smooth {
+                for group in obj.groups.iter_mut() {
+                    if group.mesh.normals.is_empty() {
+                        group.mesh.smooth_normals();
+                    }
+                }
+            }
+            obj.add_to(world, placement.as_ref());
+            return Ok(());
+        }
+        if matches!(extension.as_deref(), Some("gltf" | "glb")) {
+            let mut gltf = GltfScene::load(&file).map_err(load_error)?;
+            if let Some(material) = material {
+                gltf = gltf.with_material(material);
+            }
+            if smooth {
+                for (mesh, _) in gltf.meshes.iter_mut() {
+                    if mesh.normals.is_empty() {
+                        mesh.smooth_normals();
+                    }
+                }
+            }
+            gltf.add_to(world, placement.as_ref());
+            return Ok(());
+        }
+
+        let material = material.ok_or_else(|| {
+            self.error(
+                desc.kind.span(),
+                "only .obj and gltf meshes have materials of their own, this one needs a `material`",
+            )
+        })?;
+        let mut mesh = match extension.as_deref() {
+            Some("ply") => load_ply(&file).map_err(load_error)?,
+            Some("stl") => load_stl(&file).map_err(load_error)?,
+            _ => {
+                return Err(self.error(
+                    path.span(),
+                    "unknown mesh format, expected an .obj, .ply, .stl, .gltf or .glb file",
+                ))
+            }
+        };
+        if smooth && mesh.normals.is_empty() {
+            mesh.smooth_normals();
+        }
+        if let Some(placement) = &placement {
+            mesh.transform(placement);
+        }
+        world.push(Box::new(mesh.into_mesh(material)));
+        Ok(())
+    }
+}
+
+/// Parses a scene, files it refers to are looked up relative to the current
+/// directory.
+pub fn parse_scene(text: &str) -> Result<LoadedScene, SceneError> {
+    parse_scene_in(text, Path::new(""))
+}
+
+fn parse_scene_in(text: &str, dir: &Path) -> Result<LoadedScene, SceneError> {
+    let source = Source { text, dir };
+    let desc: SceneDesc = toml::from_str(text)
+        .map_err(|e| source.error(e.span().unwrap_or(0..0), e.message().to_string()))?;
+
+    let settings = source.settings(&desc.image)?;
+    let camera = source.camera(&desc.camera)?;
+    let environment = match &desc.environment {
+        Some(environment) => source.environment(environment)?,
+        None => Box::new(Gradient::sky()),
+    };
+
+    let mut textures = BTreeMap::new();
+    for (name, texture) in desc.textures.iter() {
+        textures.insert(name.clone(), source.texture(texture)?);
+    }
+    let mut materials = BTreeMap::new();
+    for (name, material) in desc.materials.iter() {
+        materials.insert(name.clone(), source.material(material, &textures)?);
+    }
+
+    let mut world = HittableList::new();
+    for object in desc.objects.iter() {
+        source.object(object, &materials, &mut world)?;
+    }
+
+    Ok(LoadedScene {
+        settings,
+        camera,
+        world,
+        environment,
+    })
+}
+
+pub fn load_scene(path: impl AsRef<Path>) -> Result<LoadedScene, SceneError> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    let dir = path.parent().map(PathBuf::from).unwrap_or_default();
+    parse_scene_in(&text, &dir)
+}
@@ -0,0 +1,873 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    bvh::BvhNode,
+    camera::Camera,
+    constant_medium::ConstantMedium,
+    cuboid::Cuboid,
+    environment::{Environment, Gradient, SolidColor},
+    hittable::Hittable,
+    hittable_list::HittableList,
+    image::Image,
+    linear_bvh::LinearBvh,
+    material::{Dieletric, Diffuse, DiffuseLight, Isotropic, Material, Metal},
+    matrix::Matrix,
+    mesh::TriangleMesh,
+    quad::Quad,
+    sampler::Sampler,
+    sky::PhysicalSky,
+    sphere::Sphere,
+    texture::{
+        CheckerTexture, ImageTexture, MarbleTexture, NoiseTexture, TurbulenceTexture,
+        UvCheckerTexture, WoodTexture, WorleyTexture,
+    },
+    transformed::Transformed,
+    triangle::Triangle,
+    vec3::{Color3d, Point3d, Vec3d},
+};
+
+/// Image settings a scene renders with unless they are overridden.
+#[derive(Debug, Clone, Copy)]
+pub struct SceneSettings {
+    pub aspect_ratio: f64,
+    pub image_width: usize,
+    pub samples_per_pixel: i32,
+    pub max_depth: i32,
+}
+
+/// Everything the renderer needs to know about what it renders.
+pub struct Scene {
+    pub world: Box<dyn Hittable>,
+    pub camera: Camera,
+    pub environment: Box<dyn Environment>,
+}
+
+impl Scene {
+    /// A scene under the sky gradient of the book.
+    pub fn new(world: Box<dyn Hittable>, camera: Camera) -> Self {
+        Self {
+            world,
+            camera,
+            environment: Box::new(Gradient::sky()),
+        }
+    }
+
+    pub fn with_environment(mut self, environment: impl Environment + 'static) -> Self {
+        self.environment = Box::new(environment);
+        self
+    }
+}
+
+pub type SceneBuilder = fn(aspect_ratio: f64, sampler: &mut Sampler) -> Scene;
+
+pub struct BuiltinScene {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub settings: SceneSettings,
+    build: SceneBuilder,
+}
+
+impl BuiltinScene {
+    pub fn build(&self, aspect_ratio: f64, sampler: &mut Sampler) -> Scene {
+        (self.build)(aspect_ratio, sampler)
+    }
+}
+
+const SMALL: SceneSettings = SceneSettings {
+    aspect_ratio: 16.0 / 9.0,
+    image_width: 400,
+    samples_per_pixel: 100,
+    max_depth: 50,
+};
+
+impl Default for SceneSettings {
+    fn default() -> Self {
+        SMALL
+    }
+}
+
+const BUILTIN_SCENES: [BuiltinScene; 15] = [
+    BuiltinScene {
+        name: "metal",
+        description: "diffuse sphere between a polished and a fuzzy metal sphere",
+        settings: SMALL,
+        build: metal_spheres,
+    },
+    BuiltinScene {
+        name: "glass",
+        description: "hollow glass sphere next to diffuse and shiny spheres",
+        settings: SMALL,
+        build: glass_spheres,
+    },
+    BuiltinScene {
+        name: "wide-angle",
+        description: "two touching spheres seen with a 90 degree field of view",
+        settings: SMALL,
+        build: wide_angle,
+    },
+    BuiltinScene {
+        name: "zoom",
+        description: "the glass scene seen from above",
+        settings: SMALL,
+        build: zoom,
+    },
+    BuiltinScene {
+        name: "depth-of-field",
+        description: "the glass scene with a wide aperture",
+        settings: SMALL,
+        build: depth_of_field,
+    },
+    BuiltinScene {
+        name: "final",
+        description: "the random spheres on the cover of the book",
+        settings: SceneSettings {
+            aspect_ratio: 3.0 / 2.0,
+            image_width: 1200,
+            samples_per_pixel: 500,
+            max_depth: 50,
+        },
+        build: final_scene,
+    },
+    BuiltinScene {
+        name: "sunset",
+        description: "the random spheres under a physical sky with a low sun",
+        settings: SceneSettings {
+            aspect_ratio: 3.0 / 2.0,
+            image_width: 1200,
+            samples_per_pixel: 500,
+            max_depth: 50,
+        },
+        build: sunset,
+    },
+    BuiltinScene {
+        name: "bouncing",
+        description: "the random spheres bouncing while the shutter is open",
+        settings: SMALL,
+        build: bouncing,
+    },
+    BuiltinScene {
+        name: "lights",
+        description: "spheres lit only by two glowing spheres, on a black background",
+        settings: SceneSettings {
+            samples_per_pixel: 400,
+            ..SMALL
+        },
+        build: sphere_lights,
+    },
+    BuiltinScene {
+        name: "cornell",
+        description: "the cornell box with two blocks, lit by its ceiling light",
+        settings: SceneSettings {
+            aspect_ratio: 1.0,
+            image_width: 600,
+            samples_per_pixel: 200,
+            max_depth: 50,
+        },
+        build: cornell_box,
+    },
+    BuiltinScene {
+        name: "cornell-smoke",
+        description: "the cornell box with blocks of white and black smoke",
+        settings: SceneSettings {
+            aspect_ratio: 1.0,
+            image_width: 600,
+            samples_per_pixel: 200,
+            max_depth: 50,
+        },
+        build: cornell_smoke,
+    },
+    BuiltinScene {
+        name: "meshes",
+        description: "flat, smooth and two colored triangle mesh spheres",
+        settings: SMALL,
+        build: meshes,
+    },
+    BuiltinScene {
+        name: "instances",
+        description: "thousands of pebbles sharing three meshes, around a turned block",
+        settings: SMALL,
+        build: instances,
+    },
+    BuiltinScene {
+        name: "textures",
+        description: "checkered ground, a uv checkered sphere and a banded planet",
+        settings: SMALL,
+        build: textures,
+    },
+    BuiltinScene {
+        name: "noise",
+        description: "marble, wood, cellular and turbulent noise textures",
+        settings: SMALL,
+        build: noise,
+    },
+];
+
+pub fn builtin_scenes() -> &'static [BuiltinScene] {
+    &BUILTIN_SCENES
+}
+
+pub fn find_builtin_scene(name: &str) -> Option<&'static BuiltinScene> {
+    BUILTIN_SCENES.iter().find(|scene| scene.name == name)
+}
+
+fn pinhole(lookfrom: Point3d, lookat: Point3d, vfov: f64, aspect_ratio: f64) -> Camera {
+    Camera::new(
+        lookfrom,
+        lookat,
+        Vec3d::new(0.0, 1.0, 0.0),
+        vfov,
+        aspect_ratio,
+        0.0,
+        (lookfrom - lookat).length(),
+    )
+}
+
+fn metal_spheres(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let mut world = HittableList::new();
+    let material_ground = Diffuse::new(Color3d::new(0.8, 0.8, 0.0));
+    let material_center = Diffuse::new(Color3d::new(0.7, 0.3, 0.3));
+    let material_left = Metal::new(Color3d::new(0.8, 0.8, 0.8), 0.3);
+    let material_right = Metal::new(Color3d::new(0.8, 0.6, 0.2), 1.0);
+
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, -100.5, -1.0),
+        100.0,
+        material_ground,
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, 0.0, -1.0),
+        0.5,
+        material_center,
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(-1.0, 0.0, -1.0),
+        0.5,
+        material_left,
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(1.0, 0.0, -1.0),
+        0.5,
+        material_right,
+    )));
+
+    let cam = pinhole(
+        Point3d::zero(),
+        Point3d::new(0.0, 0.0, -1.0),
+        90.0,
+        aspect_ratio,
+    );
+    Scene::new(Box::new(world), cam)
+}
+
+fn glass_world(right: Color3d) -> HittableList {
+    let mut world = HittableList::new();
+    let material_ground = Diffuse::new(Color3d::new(0.8, 0.8, 0.0));
+    let material_center = Diffuse::new(Color3d::new(0.1, 0.2, 0.5));
+    let material_left = Dieletric::new(1.5);
+    let material_right = Metal::new(right, 0.0);
+
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, -100.5, -1.0),
+        100.0,
+        material_ground,
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, 0.0, -1.0),
+        0.5,
+        material_center,
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(-1.0, 0.0, -1.0),
+        0.5,
+        material_left,
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(-1.0, 0.0, -1.0),
+        -0.45,
+        Dieletric::new(1.5),
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(1.0, 0.0, -1.0),
+        0.5,
+        material_right,
+    )));
+    world
+}
+
+fn glass_spheres(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let world = glass_world(Color3d::new(0.8, 0.6, 0.2));
+    let cam = pinhole(
+        Point3d::zero(),
+        Point3d::new(0.0, 0.0, -1.0),
+        90.0,
+        aspect_ratio,
+    );
+    Scene::new(Box::new(world), cam)
+}
+
+fn wide_angle(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let mut world = HittableList::new();
+    let material_left = Diffuse::new(Color3d::new(0.0, 0.0, 1.0));
+    let material_right = Diffuse::new(Color3d::new(1.0, 0.0, 0.0));
+
+    let r = (PI / 4.0).cos();
+
+    world.push(Box::new(Sphere::new(
+        Point3d::new(-r, 0.0, -1.0),
+        r,
+        material_left,
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(r, 0.0, -1.0),
+        r,
+        material_right,
+    )));
+
+    let cam = pinhole(
+        Point3d::zero(),
+        Point3d::new(0.0, 0.0, -1.0),
+        90.0,
+        aspect_ratio,
+    );
+    Scene::new(Box::new(world), cam)
+}
+
+fn zoom(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let world = glass_world(Color3d::new(0.6, 0.8, 0.2));
+    let cam = pinhole(
+        Point3d::new(-2.0, 2.0, 1.0),
+        Point3d::new(0.0, 0.0, -1.0),
+        90.0,
+        aspect_ratio,
+    );
+    Scene::new(Box::new(world), cam)
+}
+
+fn depth_of_field(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let world = BvhNode::new(glass_world(Color3d::new(0.8, 0.6, 0.2)));
+
+    let lookfrom = Point3d::new(3.0, 3.0, 2.0);
+    let lookat = Point3d::new(0.0, 0.0, -1.0);
+    let vup = Vec3d::new(0.0, 1.0, 0.0);
+    let dist_to_focus = (lookfrom - lookat).length();
+    let aperture = 2.0;
+
+    let cam = Camera::new(
+        lookfrom,
+        lookat,
+        vup,
+        20.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+    );
+    Scene::new(Box::new(world), cam)
+}
+
+fn final_scene(aspect_ratio: f64, sampler: &mut Sampler) -> Scene {
+    let world = LinearBvh::new(HittableList::random_scene(sampler));
+
+    let lookfrom = Point3d::new(13.0, 2.0, 3.0);
+    let lookat = Point3d::new(0.0, 0.0, 0.0);
+    let vup = Vec3d::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 10.0;
+    let aperture = 0.1;
+
+    let cam = Camera::new(
+        lookfrom,
+        lookat,
+        vup,
+        20.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+    );
+    Scene::new(Box::new(world), cam)
+}
+
+fn sunset(aspect_ratio: f64, sampler: &mut Sampler) -> Scene {
+    final_scene(aspect_ratio, sampler)
+        .with_environment(PhysicalSky::new(6.0, 70.0).with_turbidity(4.0))
+}
+
+fn bouncing(aspect_ratio: f64, sampler: &mut Sampler) -> Scene {
+    let world = LinearBvh::new(HittableList::bouncing_scene(sampler));
+
+    let cam = Camera::new(
+        Point3d::new(13.0, 2.0, 3.0),
+        Point3d::new(0.0, 0.0, 0.0),
+        Vec3d::new(0.0, 1.0, 0.0),
+        20.0,
+        aspect_ratio,
+        0.1,
+        10.0,
+    )
+    .with_shutter(0.0, 1.0);
+    Scene::new(Box::new(world), cam)
+}
+
+fn sphere_lights(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let mut world = HittableList::new();
+
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Diffuse::new(Color3d::new(0.5, 0.5, 0.5)),
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, 2.0, 0.0),
+        2.0,
+        Diffuse::new(Color3d::new(0.7, 0.3, 0.3)),
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(-4.0, 1.0, 1.5),
+        1.0,
+        Metal::new(Color3d::new(0.8, 0.8, 0.8), 0.05),
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, 7.0, 0.0),
+        1.5,
+        DiffuseLight::new(Color3d::new(4.0, 4.0, 4.0)),
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(4.0, 1.0, -2.0),
+        0.7,
+        DiffuseLight::new(Color3d::new(6.0, 3.0, 1.0)),
+    )));
+
+    let cam = pinhole(
+        Point3d::new(26.0, 3.0, 6.0),
+        Point3d::new(0.0, 2.0, 0.0),
+        20.0,
+        aspect_ratio,
+    );
+    Scene::new(Box::new(world), cam).with_environment(SolidColor::new(Color3d::zero()))
+}
+
+/// The walls and the ceiling light of the cornell box, without its blocks.
+fn cornell_walls() -> HittableList {
+    let mut world = HittableList::new();
+    let red: Arc<dyn Material> = Arc::new(Diffuse::new(Color3d::new(0.65, 0.05, 0.05)));
+    let white: Arc<dyn Material> = Arc::new(Diffuse::new(Color3d::only(0.73)));
+    let green: Arc<dyn Material> = Arc::new(Diffuse::new(Color3d::new(0.12, 0.45, 0.15)));
+    let light = DiffuseLight::new(Color3d::only(15.0));
+
+    let quad = |q: [f64; 3], u: [f64; 3], v: [f64; 3]| {
+        (
+            Point3d::new(q[0], q[1], q[2]),
+            Vec3d::new(u[0], u[1], u[2]),
+            Vec3d::new(v[0], v[1], v[2]),
+        )
+    };
+    let walls = [
+        (
+            quad([555.0, 0.0, 0.0], [0.0, 555.0, 0.0], [0.0, 0.0, 555.0]),
+            &green,
+        ),
+        (
+            quad([0.0, 0.0, 0.0], [0.0, 555.0, 0.0], [0.0, 0.0, 555.0]),
+            &red,
+        ),
+        (
+            quad([0.0, 0.0, 0.0], [555.0, 0.0, 0.0], [0.0, 0.0, 555.0]),
+            &white,
+        ),
+        (
+            quad(
+                [555.0, 555.0, 555.0],
+                [-555.0, 0.0, 0.0],
+                [0.0, 0.0, -555.0],
+            ),
+            &white,
+        ),
+        (
+            quad([0.0, 0.0, 555.0], [555.0, 0.0, 0.0], [0.0, 555.0, 0.0]),
+            &white,
+        ),
+    ];
+    for ((q, u, v), material) in walls {
+        world.push(Box::new(Quad::new(q, u, v, material.clone())));
+    }
+    world.push(Box::new(Quad::new(
+        Point3d::new(343.0, 554.0, 332.0),
+        Vec3d::new(-130.0, 0.0, 0.0),
+        Vec3d::new(0.0, 0.0, -105.0),
+        light,
+    )));
+    world
+}
+
+fn cornell_camera(aspect_ratio: f64) -> Camera {
+    pinhole(
+        Point3d::new(278.0, 278.0, -800.0),
+        Point3d::new(278.0, 278.0, 0.0),
+        40.0,
+        aspect_ratio,
+    )
+}
+
+fn cornell_box(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let mut world = cornell_walls();
+    let white: Arc<dyn Material> = Arc::new(Diffuse::new(Color3d::only(0.73)));
+    world.push(Box::new(Cuboid::new(
+        Point3d::new(130.0, 0.0, 65.0),
+        Point3d::new(295.0, 165.0, 230.0),
+        white.clone(),
+    )));
+    world.push(Box::new(Cuboid::new(
+        Point3d::new(265.0, 0.0, 295.0),
+        Point3d::new(430.0, 330.0, 460.0),
+        white,
+    )));
+
+    Scene::new(
+        Box::new(LinearBvh::new(world)),
+        cornell_camera(aspect_ratio),
+    )
+    .with_environment(SolidColor::new(Color3d::zero()))
+}
+
+fn cornell_smoke(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let mut world = cornell_walls();
+    // the material of a boundary does not matter
+    let boundary =
+        |min: Point3d, max: Point3d| Cuboid::new(min, max, Diffuse::new(Color3d::zero()));
+    world.push(Box::new(ConstantMedium::new(
+        boundary(
+            Point3d::new(130.0, 0.0, 65.0),
+            Point3d::new(295.0, 165.0, 230.0),
+        ),
+        0.01,
+        Isotropic::new(Color3d::only(1.0)),
+    )));
+    world.push(Box::new(ConstantMedium::new(
+        boundary(
+            Point3d::new(265.0, 0.0, 295.0),
+            Point3d::new(430.0, 330.0, 460.0),
+        ),
+        0.01,
+        Isotropic::new(Color3d::zero()),
+    )));
+
+    Scene::new(
+        Box::new(LinearBvh::new(world)),
+        cornell_camera(aspect_ratio),
+    )
+    .with_environment(SolidColor::new(Color3d::zero()))
+}
+
+/// Vertices and faces of a unit icosahedron with every face split into four
+/// `subdivisions` times, pushed out onto the sphere.
+fn icosphere(subdivisions: usize) -> (Vec<Point3d>, Vec<[usize; 3]>) {
+    let t = (1.0 + 5f64.sqrt()) / 2.0;
+    let mut positions: Vec<Point3d> = [
+        (-1.0, t, 0.0),
+        (1.0, t, 0.0),
+        (-1.0, -t, 0.0),
+        (1.0, -t, 0.0),
+        (0.0, -1.0, t),
+        (0.0, 1.0, t),
+        (0.0, -1.0, -t),
+        (0.0, 1.0, -t),
+        (t, 0.0, -1.0),
+        (t, 0.0, 1.0),
+        (-t, 0.0, -1.0),
+        (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Point3d::new(x, y, z).unit_vector())
+    .collect();
+    let mut faces = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // midpoints are shared by the two faces along an edge
+        let mut midpoints = std::collections::HashMap::new();
+        let mut midpoint = |a: usize, b: usize, positions: &mut Vec<Point3d>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push((0.5 * (positions[a] + positions[b])).unit_vector());
+                positions.len() - 1
+            })
+        };
+        let mut next = Vec::with_capacity(4 * faces.len());
+        for [a, b, c] in faces {
+            let ab = midpoint(a, b, &mut positions);
+            let bc = midpoint(b, c, &mut positions);
+            let ca = midpoint(c, a, &mut positions);
+            next.extend([[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+        }
+        faces = next;
+    }
+    (positions, faces)
+}
+
+fn meshes(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let mut world = HittableList::new();
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, -100.5, -1.0),
+        100.0,
+        Diffuse::new(Color3d::new(0.8, 0.8, 0.0)),
+    )));
+
+    let (unit, faces) = icosphere(2);
+    let placed = |x: f64| -> Vec<Point3d> {
+        unit.iter()
+            .map(|p| 0.5 * *p + Point3d::new(x, 0.0, -1.0))
+            .collect()
+    };
+
+    // flat shading shows every face
+    world.push(Box::new(TriangleMesh::new(
+        placed(-1.1),
+        faces.clone(),
+        Diffuse::new(Color3d::new(0.7, 0.3, 0.3)),
+    )));
+    // the normals of the sphere the mesh approximates hide them
+    world.push(Box::new(
+        TriangleMesh::new(
+            placed(0.0),
+            faces.clone(),
+            Metal::new(Color3d::new(0.8, 0.8, 0.8), 0.0),
+        )
+        .with_normals(unit.clone()),
+    ));
+    // every fourth face, the middle one of each split, in a second color
+    let stripes = (0..faces.len()).map(|i| usize::from(i % 4 == 3)).collect();
+    let colors: Vec<Arc<dyn Material>> = vec![
+        Arc::new(Diffuse::new(Color3d::new(0.1, 0.2, 0.5))),
+        Arc::new(Metal::new(Color3d::new(0.8, 0.6, 0.2), 0.2)),
+    ];
+    world.push(Box::new(
+        TriangleMesh::new(placed(1.1), faces, colors[0].clone())
+            .with_smooth_normals()
+            .with_face_materials(colors, stripes),
+    ));
+
+    world.push(Box::new(Triangle::new(
+        Point3d::new(-1.6, -0.5, -2.2),
+        Point3d::new(1.6, -0.5, -2.2),
+        Point3d::new(0.0, 1.2, -2.4),
+        Diffuse::new(Color3d::new(0.2, 0.6, 0.3)),
+    )));
+
+    let cam = pinhole(
+        Point3d::new(0.0, 0.6, 2.2),
+        Point3d::new(0.0, 0.0, -1.0),
+        40.0,
+        aspect_ratio,
+    );
+    Scene::new(Box::new(LinearBvh::new(world)), cam)
+}
+
+fn instances(aspect_ratio: f64, sampler: &mut Sampler) -> Scene {
+    let mut world = HittableList::new();
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Diffuse::new(Color3d::new(0.5, 0.45, 0.4)),
+    )));
+
+    // every pebble is one of these, stored once however often it is placed
+    let (unit, faces) = icosphere(3);
+    let materials: [Arc<dyn Material>; 3] = [
+        Arc::new(Diffuse::new(Color3d::new(0.6, 0.55, 0.5))),
+        Arc::new(Diffuse::new(Color3d::new(0.3, 0.3, 0.35))),
+        Arc::new(Metal::new(Color3d::new(0.8, 0.6, 0.4), 0.3)),
+    ];
+    let pebbles = materials.map(|material| {
+        Arc::new(
+            TriangleMesh::new(unit.clone(), faces.clone(), material).with_normals(unit.clone()),
+        )
+    });
+
+    for i in -40..40 {
+        for j in -40..40 {
+            let center = Point3d::new(
+                0.25 * (i as f64 + sampler.random_double()),
+                0.0,
+                0.25 * (j as f64 + sampler.random_double()),
+            );
+            if center.length() < 1.5 {
+                continue;
+            }
+            let radius = sampler.random_range(0.04, 0.1);
+            // flat pebbles lie on the ground, turned every which way about y
+            let scale = Vec3d::new(
+                radius * sampler.random_range(1.0, 1.8),
+                radius * sampler.random_range(0.4, 0.8),
+                radius,
+            );
+            let matrix = Matrix::translation(center + Vec3d::new(0.0, 0.6 * scale.y, 0.0))
+                * Matrix::rotation_y(sampler.random_range(0.0, 360.0))
+                * Matrix::scaling(scale);
+            let pebble = &pebbles[(sampler.random_double() * 3.0) as usize];
+            world.push(Box::new(Transformed::new(pebble.clone(), matrix)));
+        }
+    }
+
+    // boxes are axis aligned, a matrix turns and tilts this one
+    let block = Cuboid::new(
+        Point3d::only(-0.5),
+        Point3d::only(0.5),
+        Metal::new(Color3d::new(0.7, 0.7, 0.75), 0.05),
+    );
+    world.push(Box::new(Transformed::new(
+        block,
+        Matrix::translation(Vec3d::new(0.0, 0.75, 0.0))
+            * Matrix::rotation(Vec3d::new(1.0, 0.0, 1.0), 20.0)
+            * Matrix::rotation_y(30.0),
+    )));
+
+    let cam = pinhole(
+        Point3d::new(4.0, 2.5, 6.0),
+        Point3d::new(0.0, 0.4, 0.0),
+        30.0,
+        aspect_ratio,
+    );
+    Scene::new(Box::new(LinearBvh::new(world)), cam)
+}
+
+fn textures(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let mut world = HittableList::new();
+
+    // cubes cut through the ground sphere, squares on its top
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Diffuse::textured(CheckerTexture::from_colors(
+            0.5,
+            Color3d::new(0.2, 0.3, 0.1),
+            Color3d::new(0.9, 0.9, 0.9),
+        )),
+    )));
+    // the squares crowd together at the poles, they follow u and v
+    world.push(Box::new(Sphere::new(
+        Point3d::new(-2.2, 1.0, 0.0),
+        1.0,
+        Diffuse::textured(UvCheckerTexture::from_colors(
+            16.0,
+            8.0,
+            Color3d::new(0.8, 0.1, 0.1),
+            Color3d::new(0.9, 0.8, 0.6),
+        )),
+    )));
+
+    // bands of a gas giant, wavy along the longitude
+    let (width, height) = (256, 128);
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let u = x as f64 / width as f64;
+            let v = y as f64 / height as f64;
+            let band = (v * 24.0 + 0.6 * (u * 4.0 * PI).sin() * (v * PI).sin()).sin();
+            let t = 0.5 + 0.5 * band;
+            pixels
+                .push(t * Color3d::new(0.9, 0.75, 0.55) + (1.0 - t) * Color3d::new(0.45, 0.2, 0.1));
+        }
+    }
+    let image = Image {
+        width,
+        height,
+        pixels,
+    };
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, 1.0, 0.0),
+        1.0,
+        Diffuse::textured(ImageTexture::new(image)),
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(2.2, 1.0, 0.0),
+        1.0,
+        Metal::textured(
+            UvCheckerTexture::from_colors(
+                4.0,
+                1.0,
+                Color3d::new(0.8, 0.8, 0.8),
+                Color3d::new(0.8, 0.6, 0.2),
+            ),
+            0.1,
+        ),
+    )));
+
+    let cam = pinhole(
+        Point3d::new(0.0, 2.5, 9.0),
+        Point3d::new(0.0, 0.8, 0.0),
+        30.0,
+        aspect_ratio,
+    );
+    Scene::new(Box::new(LinearBvh::new(world)), cam)
+}
+
+fn noise(aspect_ratio: f64, _sampler: &mut Sampler) -> Scene {
+    let mut world = HittableList::new();
+
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Diffuse::textured(
+            TurbulenceTexture::new(1, 2.0)
+                .with_colors(Color3d::new(0.35, 0.4, 0.25), Color3d::new(0.7, 0.7, 0.6)),
+        ),
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, 1.0, 3.3),
+        1.0,
+        Diffuse::textured(NoiseTexture::new(2, 4.0)),
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, 1.0, 1.1),
+        1.0,
+        Diffuse::textured(
+            MarbleTexture::new(3, 6.0)
+                .with_colors(Color3d::new(0.2, 0.22, 0.25), Color3d::new(0.9, 0.9, 0.88)),
+        ),
+    )));
+    // the rings run around the y axis, so the block is moved rather than
+    // built away from it
+    world.push(Box::new(Transformed::new(
+        Cuboid::new(
+            Point3d::new(-0.8, 0.0, -0.8),
+            Point3d::new(0.8, 1.6, 0.8),
+            Diffuse::textured(WoodTexture::new(4, 6.0)),
+        ),
+        Matrix::translation(Vec3d::new(0.0, 0.0, -1.1)) * Matrix::rotation_y(30.0),
+    )));
+    world.push(Box::new(Sphere::new(
+        Point3d::new(0.0, 1.0, -3.3),
+        1.0,
+        Metal::textured(
+            WorleyTexture::new(5, 3.0)
+                .with_colors(Color3d::new(0.9, 0.7, 0.3), Color3d::new(0.3, 0.2, 0.1)),
+            0.2,
+        ),
+    )));
+
+    // from the side, the veins of marble run across z
+    let cam = pinhole(
+        Point3d::new(10.5, 4.5, 0.0),
+        Point3d::new(0.0, 0.8, 0.0),
+        30.0,
+        aspect_ratio,
+    );
+    Scene::new(Box::new(LinearBvh::new(world)), cam)
+}
@@ -0,0 +1,275 @@
+//! Analytic daylight after Preetham, Shirley and Smits, "A Practical Analytic
+//! Model for Daylight" (1999), with a sun disk that can be sampled directly.
+
+use std::f64::consts::PI;
+
+use crate::{
+    environment::Environment,
+    sampler::Sampler,
+    utils::degrees_to_radians,
+    vec3::{Color3d, Vec3d},
+};
+
+/// the sun seen from earth is about half a degree across
+const SUN_ANGULAR_RADIUS: f64 = 0.00465;
+/// luminance of the sun outside the atmosphere, in kcd/m²
+const SUN_LUMINANCE: f64 = 1.6e6;
+/// kcd/m² to the radiance units of the renderer, makes a clear noon sky
+/// roughly as bright as the gradient of the book
+const LUMINANCE_SCALE: f64 = 0.06;
+
+/// Sky and sun for a sun at `elevation` degrees above the horizon and
+/// `azimuth` degrees from `-z` towards `+x`. Directions below the horizon
+/// see a diffuse ground of color `ground_albedo` lit by both.
+#[derive(Debug, Clone)]
+pub struct PhysicalSky {
+    elevation: f64,
+    azimuth: f64,
+    turbidity: f64,
+    ground_albedo: Color3d,
+    intensity: f64,
+
+    /// zenith angle of the sun as far as the sky is concerned
+    theta_s: f64,
+    /// intensity, dimmed during twilight
+    sky_scale: f64,
+    sun_direction: Vec3d,
+    sun_radiance: Color3d,
+    /// perez coefficients of luminance and the two chromaticities
+    perez: [[f64; 5]; 3],
+    /// luminance and chromaticity at the zenith
+    zenith: [f64; 3],
+    ground: Color3d,
+}
+
+impl PhysicalSky {
+    pub fn new(elevation: f64, azimuth: f64) -> Self {
+        let mut sky = Self {
+            elevation,
+            azimuth,
+            turbidity: 3.0,
+            ground_albedo: Color3d::only(0.3),
+            intensity: 1.0,
+            theta_s: 0.0,
+            sky_scale: 1.0,
+            sun_direction: Vec3d::new(0.0, 1.0, 0.0),
+            sun_radiance: Color3d::zero(),
+            perez: [[0.0; 5]; 3],
+            zenith: [0.0; 3],
+            ground: Color3d::zero(),
+        };
+        sky.update();
+        sky
+    }
+
+    /// Haziness of the air, 2 is very clear and 10 hazy. The model is only
+    /// fitted between those, other values are clamped.
+    pub fn with_turbidity(mut self, turbidity: f64) -> Self {
+        self.turbidity = turbidity;
+        self.update();
+        self
+    }
+
+    pub fn with_ground_albedo(mut self, ground_albedo: Color3d) -> Self {
+        self.ground_albedo = ground_albedo;
+        self.update();
+        self
+    }
+
+    /// Scales the radiance of sky, sun and ground.
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self.update();
+        self
+    }
+
+    pub fn sun_direction(&self) -> Vec3d {
+        self.sun_direction
+    }
+
+    fn update(&mut self) {
+        let t = self.turbidity.clamp(1.7, 10.0);
+        let elevation = degrees_to_radians(self.elevation);
+        let azimuth = degrees_to_radians(self.azimuth);
+        self.sun_direction = Vec3d::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        );
+
+        // the model is not defined for a sun below the horizon
+        let theta_s = (PI / 2.0 - elevation).clamp(0.0, PI / 2.0);
+        self.theta_s = theta_s;
+        // so it stays at the sunset sky and fades out over civil twilight
+        let twilight = (1.0 + self.elevation / 6.0).clamp(0.0, 1.0);
+        self.sky_scale = self.intensity * twilight;
+
+        self.perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let luminance = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.0);
+        let (s1, s2, s3) = (theta_s, theta_s * theta_s, theta_s * theta_s * theta_s);
+        let x = t * t * (0.00166 * s3 - 0.00375 * s2 + 0.00209 * s1)
+            + t * (-0.02903 * s3 + 0.06377 * s2 - 0.03202 * s1 + 0.00394)
+            + (0.11693 * s3 - 0.21196 * s2 + 0.06052 * s1 + 0.25886);
+        let y = t * t * (0.00275 * s3 - 0.00610 * s2 + 0.00317 * s1)
+            + t * (-0.04214 * s3 + 0.08970 * s2 - 0.04153 * s1 + 0.00516)
+            + (0.15346 * s3 - 0.26756 * s2 + 0.06670 * s1 + 0.26688);
+        self.zenith = [luminance, x, y];
+
+        self.sun_radiance = if elevation > -SUN_ANGULAR_RADIUS {
+            sun_transmittance(t, theta_s) * (SUN_LUMINANCE * LUMINANCE_SCALE * self.intensity)
+        } else {
+            Color3d::zero()
+        };
+
+        // irradiance on the ground, from the sun and numerically from the sky
+        let mut irradiance = self.sun_radiance
+            * (2.0 * PI * (1.0 - SUN_ANGULAR_RADIUS.cos()) * self.sun_direction.y.max(0.0));
+        const STEPS_THETA: usize = 16;
+        const STEPS_PHI: usize = 32;
+        let d_theta = PI / 2.0 / STEPS_THETA as f64;
+        let d_phi = 2.0 * PI / STEPS_PHI as f64;
+        for i in 0..STEPS_THETA {
+            let theta = (i as f64 + 0.5) * d_theta;
+            for j in 0..STEPS_PHI {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let direction = Vec3d::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                irradiance +=
+                    self.sky_radiance(&direction) * (theta.cos() * theta.sin() * d_theta * d_phi);
+            }
+        }
+        self.ground = self.ground_albedo * irradiance / PI;
+    }
+
+    /// Radiance of the sky alone, `direction` is a unit vector above the
+    /// horizon.
+    fn sky_radiance(&self, direction: &Vec3d) -> Color3d {
+        let theta_s = self.theta_s;
+        let cos_theta = direction.y.max(0.01);
+        let cos_gamma = direction.dot(&self.sun_direction).clamp(-1.0, 1.0);
+        let gamma = cos_gamma.acos();
+
+        let perez = |c: &[f64; 5], cos_theta: f64, gamma: f64, cos_gamma: f64| {
+            (1.0 + c[0] * (c[1] / cos_theta).exp())
+                * (1.0 + c[2] * (c[3] * gamma).exp() + c[4] * cos_gamma * cos_gamma)
+        };
+        let mut yxy = [0.0; 3];
+        for (k, c) in self.perez.iter().enumerate() {
+            yxy[k] = self.zenith[k] * perez(c, cos_theta, gamma, cos_gamma)
+                / perez(c, 1.0, theta_s, theta_s.cos());
+        }
+
+        let [luminance, x, y] = yxy;
+        if y <= 0.0 {
+            return Color3d::zero();
+        }
+        let luminance = luminance * LUMINANCE_SCALE * self.sky_scale;
+        xyz_to_rgb(x / y * luminance, luminance, (1.0 - x - y) / y * luminance)
+    }
+
+    fn sun_visible(&self) -> bool {
+        self.sun_radiance.x > 0.0 || self.sun_radiance.y > 0.0 || self.sun_radiance.z > 0.0
+    }
+}
+
+impl Environment for PhysicalSky {
+    fn radiance(&self, direction: &Vec3d) -> Color3d {
+        let d = direction.unit_vector();
+        if d.y < 0.0 {
+            return self.ground;
+        }
+
+        let mut radiance = self.sky_radiance(&d);
+        if d.dot(&self.sun_direction) >= SUN_ANGULAR_RADIUS.cos() {
+            radiance += self.sun_radiance;
+        }
+        radiance
+    }
+
+    /// Samples the sun disk, the sky is left to the directions materials
+    /// scatter into.
+    fn sample(&self, sampler: &mut Sampler) -> Option<(Vec3d, Color3d, f64)> {
+        if !self.sun_visible() {
+            return None;
+        }
+
+        // uniform in the cone around the sun direction
+        let cos_max = SUN_ANGULAR_RADIUS.cos();
+        let cos_theta = 1.0 - sampler.random_double() * (1.0 - cos_max);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let phi = 2.0 * PI * sampler.random_double();
+
+        let w = self.sun_direction;
+        let a = if w.x.abs() > 0.9 {
+            Vec3d::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3d::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(&a).unit_vector();
+        let u = w.cross(&v);
+        let direction = (u * (sin_theta * phi.cos()) + v * (sin_theta * phi.sin()) + w * cos_theta)
+            .unit_vector();
+
+        let pdf = 1.0 / (2.0 * PI * (1.0 - cos_max));
+        Some((direction, self.radiance(&direction), pdf))
+    }
+
+    fn pdf(&self, direction: &Vec3d) -> f64 {
+        let cos_max = SUN_ANGULAR_RADIUS.cos();
+        if self.sun_visible() && direction.unit_vector().dot(&self.sun_direction) >= cos_max {
+            1.0 / (2.0 * PI * (1.0 - cos_max))
+        } else {
+            0.0
+        }
+    }
+}
+
+fn xyz_to_rgb(x: f64, y: f64, z: f64) -> Color3d {
+    Color3d::new(
+        (3.2406 * x - 1.5372 * y - 0.4986 * z).max(0.0),
+        (-0.9689 * x + 1.8758 * y + 0.0415 * z).max(0.0),
+        (0.0557 * x - 0.2040 * y + 1.0570 * z).max(0.0),
+    )
+}
+
+// rayleigh and aerosol extinction of sunlight along the air mass at zenith
+// angle `theta_s`, for red, green and blue wavelengths
+fn sun_transmittance(turbidity: f64, theta_s: f64) -> Color3d {
+    let zenith_degrees = theta_s.to_degrees();
+    let air_mass = 1.0 / (theta_s.cos() + 0.15 * (93.885 - zenith_degrees).powf(-1.253));
+    let beta = 0.04608 * turbidity - 0.04586;
+
+    let channel = |wavelength: f64| {
+        let rayleigh = 0.008735 * wavelength.powf(-4.08);
+        let aerosol = beta * wavelength.powf(-1.3);
+        (-air_mass * (rayleigh + aerosol)).exp()
+    };
+    Color3d::new(channel(0.68), channel(0.55), channel(0.44))
+}
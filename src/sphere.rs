@@ -1,9 +1,11 @@
-use std::borrow::Borrow;
+use std::{borrow::Borrow, f64::consts::PI};
 
 use crate::{
+    aabb::Aabb,
     hittable::{HitRecord, Hittable},
     material::Material,
     ray::Ray,
+    sampler::Sampler,
     vec3::{Point3d, Vec3d},
 };
 
@@ -27,7 +29,21 @@ impl<M: Material> Sphere<M> {
     }
 }
 
-fn solve_sphere_equation(
+/// Texture coordinates of a point on the unit sphere: `u` goes around the
+/// y axis from -x, `v` from the bottom to the top.
+pub fn sphere_uv(p: &Point3d) -> (f64, f64) {
+    let theta = (-p.y).clamp(-1.0, 1.0).acos();
+    let phi = (-p.z).atan2(p.x) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+/// The texture coordinates of a sphere cover its whole surface once, so
+/// they change this fast on average.
+pub fn sphere_uv_density(radius: f64) -> f64 {
+    1.0 / (2.0 * radius.abs() * PI.sqrt())
+}
+
+pub(crate) fn solve_sphere_equation(
     ray: &Ray,
     center: Point3d,
     radius: f64,
@@ -58,9 +74,16 @@ fn solve_sphere_equation(
 }
 
 impl<M: Material> Hittable for Sphere<M> {
-    fn hit(&self, ray: &crate::ray::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _sampler: &mut Sampler,
+    ) -> Option<HitRecord<'_>> {
         solve_sphere_equation(ray, self.center, self.radius, t_min, t_max).map(
             |(root, point, outward_normal)| {
+                let (u, v) = sphere_uv(&((point - self.center) / self.radius.abs()));
                 HitRecord::new_with_face_normal(
                     root,
                     point,
@@ -68,7 +91,14 @@ impl<M: Material> Hittable for Sphere<M> {
                     self.material.borrow(),
                     ray,
                 )
+                .with_uv(u, v)
+                .with_footprint(ray, sphere_uv_density(self.radius))
             },
         )
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3d::only(self.radius.abs());
+        Some(Aabb::new(self.center - r, self.center + r))
+    }
 }
@@ -0,0 +1,270 @@
+//! Stereolithography `.stl` meshes, binary or ascii.
+//!
+//! Every facet stores its own corners, so corners at the same position are
+//! merged into shared vertices. The facet normals of the file are ignored
+//! in favour of the winding of the corners, which is counterclockwise seen
+//! from outside.
+
+use std::{collections::HashMap, convert::TryInto, fs, io, path::Path};
+
+use crate::{
+    mesh::{triangulate, MeshData},
+    vec3::Point3d,
+};
+
+/// 80 bytes of header and the number of triangles.
+const HEADER_SIZE: usize = 84;
+/// normal, three corners and a two byte attribute
+const TRIANGLE_SIZE: usize = 50;
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Gives corners at the same position the same vertex.
+#[derive(Default)]
+struct Welder {
+    mesh: MeshData,
+    vertices: HashMap<[u64; 3], usize>,
+}
+
+impl Welder {
+    fn vertex(&mut self, p: Point3d) -> usize {
+        // adding 0 turns -0 into 0, so that both get the same key
+        let key = [
+            (p.x + 0.0).to_bits(),
+            (p.y + 0.0).to_bits(),
+            (p.z + 0.0).to_bits(),
+        ];
+        let positions = &mut self.mesh.positions;
+        *self.vertices.entry(key).or_insert_with(|| {
+            positions.push(p);
+            positions.len() - 1
+        })
+    }
+
+    fn polygon(&mut self, corners: &[Point3d]) -> io::Result<()> {
+        if corners
+            .iter()
+            .any(|p| !(p.x.is_finite() && p.y.is_finite() && p.z.is_finite()))
+        {
+            return Err(invalid("stl vertex positions must be finite"));
+        }
+        let vertices: Vec<usize> = corners.iter().map(|&p| self.vertex(p)).collect();
+        for [a, b, c] in triangulate(corners) {
+            let face = [vertices[a], vertices[b], vertices[c]];
+            // facets that collapse when merging their corners cover nothing
+            if face[0] != face[1] && face[1] != face[2] && face[2] != face[0] {
+                self.mesh.faces.push(face);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_binary(data: &[u8]) -> io::Result<MeshData> {
+    let count = u32::from_le_bytes(data[80..HEADER_SIZE].try_into().unwrap()) as usize;
+    let needed = count
+        .checked_mul(TRIANGLE_SIZE)
+        .and_then(|size| size.checked_add(HEADER_SIZE))
+        .filter(|&size| size <= data.len())
+        .ok_or_else(|| {
+            invalid(format!(
+                "binary stl with {} triangles is cut off after {} bytes",
+                count,
+                data.len()
+            ))
+        })?;
+
+    let mut welder = Welder::default();
+    for triangle in data[HEADER_SIZE..needed].chunks_exact(TRIANGLE_SIZE) {
+        let float = |i: usize| {
+            let offset = 12 + 4 * i;
+            f32::from_le_bytes(triangle[offset..offset + 4].try_into().unwrap()) as f64
+        };
+        let corner = |c: usize| Point3d::new(float(3 * c), float(3 * c + 1), float(3 * c + 2));
+        welder.polygon(&[corner(0), corner(1), corner(2)])?;
+    }
+    Ok(welder.mesh)
+}
+
+fn number(words: &mut std::str::SplitAsciiWhitespace) -> io::Result<f64> {
+    match words.next() {
+        Some(word) => word
+            .parse()
+            .map_err(|_| invalid(format!("invalid number '{}' in the stl data", word))),
+        None => Err(invalid("the stl data ends early")),
+    }
+}
+
+fn read_ascii(text: &str) -> io::Result<MeshData> {
+    let mut welder = Welder::default();
+    let mut corners: Option<Vec<Point3d>> = None;
+    // a file cut off between facets still has to miss its `endsolid`
+    let mut ended = false;
+    let mut words = text.split_ascii_whitespace();
+
+    while let Some(word) = words.next() {
+        match word {
+            "facet" => ended = false,
+            "endsolid" => ended = true,
+            "outer" => {
+                if corners.is_some() {
+                    return Err(invalid("stl loop inside another loop"));
+                }
+                corners = Some(vec![]);
+            }
+            "vertex" => {
+                let p = Point3d::new(
+                    number(&mut words)?,
+                    number(&mut words)?,
+                    number(&mut words)?,
+                );
+                corners
+                    .as_mut()
+                    .ok_or_else(|| invalid("stl vertex outside of a loop"))?
+                    .push(p);
+            }
+            "endloop" => {
+                let corners = corners
+                    .take()
+                    .ok_or_else(|| invalid("stl `endloop` without a loop"))?;
+                if corners.len() < 3 {
+                    return Err(invalid("stl loop with less than three vertices"));
+                }
+                welder.polygon(&corners)?;
+            }
+            // numbers of facet normals and names are skipped along with
+            // the other keywords
+            _ => {}
+        }
+    }
+    if corners.is_some() {
+        return Err(invalid("the stl data ends inside a loop"));
+    }
+    if !ended {
+        return Err(invalid("the stl data ends before `endsolid`"));
+    }
+    Ok(welder.mesh)
+}
+
+/// Reads an stl file that is entirely in `data`.
+pub fn read_stl(data: &[u8]) -> io::Result<MeshData> {
+    // binary files may start with "solid" too, but then their size matches
+    // the number of triangles they claim to have
+    let binary_size = data.get(80..HEADER_SIZE).map(|count| {
+        HEADER_SIZE as u64
+            + TRIANGLE_SIZE as u64 * u32::from_le_bytes(count.try_into().unwrap()) as u64
+    });
+    let starts_as_text = data
+        .iter()
+        .skip_while(|b| b.is_ascii_whitespace())
+        .take(5)
+        .eq(b"solid".iter());
+    if starts_as_text && binary_size != Some(data.len() as u64) {
+        // or they have something trailing
+        if let Ok(text) = std::str::from_utf8(data) {
+            return read_ascii(text);
+        }
+    }
+    if binary_size.is_none() {
+        return Err(invalid("not an stl file, it is too short for the header"));
+    }
+    read_binary(data)
+}
+
+pub fn load_stl(path: impl AsRef<Path>) -> io::Result<MeshData> {
+    let path = path.as_ref();
+    let data = fs::read(path).map_err(|e| {
+        io::Error::new(e.kind(), format!("cannot open '{}': {}", path.display(), e))
+    })?;
+    read_stl(&data).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TETRAHEDRON: [[[f32; 3]; 3]; 4] = [
+        [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]],
+        [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+        [[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]],
+        [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    ];
+
+    fn ascii() -> String {
+        let mut text = String::from("solid tetrahedron\n");
+        for facet in TETRAHEDRON.iter() {
+            text += "  facet normal 0 0 0\n    outer loop\n";
+            for p in facet {
+                text += &format!("      vertex {} {} {}\n", p[0], p[1], p[2]);
+            }
+            text += "    endloop\n  endfacet\n";
+        }
+        text + "endsolid tetrahedron\n"
+    }
+
+    /// The binary file, with a header that starts like an ascii one.
+    fn binary() -> Vec<u8> {
+        let mut data = b"solid but binary".to_vec();
+        data.resize(80, b' ');
+        data.extend((TETRAHEDRON.len() as u32).to_le_bytes());
+        for facet in TETRAHEDRON.iter() {
+            data.extend([0; 12]);
+            for value in facet.iter().flatten() {
+                data.extend(value.to_le_bytes());
+            }
+            data.extend([0; 2]);
+        }
+        data
+    }
+
+    fn assert_tetrahedron(mesh: &MeshData) {
+        // the twelve corners are welded into four vertices
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.faces.len(), 4);
+        for (face, facet) in mesh.faces.iter().zip(TETRAHEDRON.iter()) {
+            for (&i, p) in face.iter().zip(facet) {
+                let p = Point3d::new(p[0] as f64, p[1] as f64, p[2] as f64);
+                assert_eq!(mesh.positions[i], p);
+            }
+        }
+    }
+
+    #[test]
+    fn reads_ascii() {
+        assert_tetrahedron(&read_stl(ascii().as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn reads_binary() {
+        assert_eq!(binary().len(), HEADER_SIZE + 4 * TRIANGLE_SIZE);
+        assert_tetrahedron(&read_stl(&binary()).unwrap());
+    }
+
+    #[test]
+    fn rejects_truncated_binary() {
+        let data = binary();
+        let error = read_stl(&data[..data.len() - 1]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(read_stl(&data[..HEADER_SIZE + TRIANGLE_SIZE]).is_err());
+        assert!(read_stl(&data[..50]).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_ascii() {
+        let text = ascii();
+        let cut = text.rfind("vertex").unwrap();
+        assert!(read_stl(&text.as_bytes()[..cut]).is_err());
+        assert!(read_stl(&text.as_bytes()[..cut + 10]).is_err());
+        let cut = text.rfind("facet normal").unwrap();
+        assert!(read_stl(&text.as_bytes()[..cut]).is_err());
+    }
+
+    #[test]
+    fn rejects_loops_with_missing_vertices() {
+        let text = "solid s\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\n\
+                    endloop\nendfacet\nendsolid s\n";
+        assert!(read_stl(text.as_bytes()).is_err());
+    }
+}
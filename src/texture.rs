@@ -0,0 +1,746 @@
+//! Colors that vary over surfaces, for the albedo of materials.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError},
+};
+
+use crate::{
+    hittable::HitRecord,
+    image::Image,
+    noise::{Perlin, Worley},
+    vec3::{Color3d, Point3d},
+};
+
+pub trait Texture: Send + Sync {
+    /// Color at texture coordinates `u`, `v` of the hit point `p`.
+    fn value(&self, u: f64, v: f64, p: &Point3d) -> Color3d;
+
+    /// Color over the footprint of the ray at the hit, see
+    /// [`HitRecord::uv_width`]. Textures that are not filtered take the
+    /// value at its center.
+    fn sample(&self, rec: &HitRecord) -> Color3d {
+        self.value(rec.u, rec.v, &rec.p)
+    }
+}
+
+impl Texture for Box<dyn Texture> {
+    fn value(&self, u: f64, v: f64, p: &Point3d) -> Color3d {
+        self.as_ref().value(u, v, p)
+    }
+
+    fn sample(&self, rec: &HitRecord) -> Color3d {
+        self.as_ref().sample(rec)
+    }
+}
+
+impl Texture for Arc<dyn Texture> {
+    fn value(&self, u: f64, v: f64, p: &Point3d) -> Color3d {
+        self.as_ref().value(u, v, p)
+    }
+
+    fn sample(&self, rec: &HitRecord) -> Color3d {
+        self.as_ref().sample(rec)
+    }
+}
+
+/// The same color everywhere.
+#[derive(Debug, Clone, Copy)]
+pub struct SolidTexture {
+    color: Color3d,
+}
+
+impl SolidTexture {
+    pub fn new(color: Color3d) -> Self {
+        Self { color }
+    }
+}
+
+impl Texture for SolidTexture {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3d) -> Color3d {
+        self.color
+    }
+}
+
+/// Cubes of `scale` filling space, alternating between `even` and `odd`.
+/// Solid, so that it does not depend on how a surface is parameterized.
+#[derive(Clone)]
+pub struct CheckerTexture<E: Texture, O: Texture> {
+    inv_scale: f64,
+    even: E,
+    odd: O,
+}
+
+impl<E: Texture, O: Texture> CheckerTexture<E, O> {
+    pub fn new(scale: f64, even: E, odd: O) -> Self {
+        Self {
+            inv_scale: 1.0 / scale,
+            even,
+            odd,
+        }
+    }
+}
+
+impl CheckerTexture<SolidTexture, SolidTexture> {
+    pub fn from_colors(scale: f64, even: Color3d, odd: Color3d) -> Self {
+        Self::new(scale, SolidTexture::new(even), SolidTexture::new(odd))
+    }
+}
+
+impl<E: Texture, O: Texture> CheckerTexture<E, O> {
+    fn is_even(&self, p: &Point3d) -> bool {
+        let cell = |x: f64| (self.inv_scale * x).floor() as i64;
+        (cell(p.x) + cell(p.y) + cell(p.z)).rem_euclid(2) == 0
+    }
+}
+
+impl<E: Texture, O: Texture> Texture for CheckerTexture<E, O> {
+    fn value(&self, u: f64, v: f64, p: &Point3d) -> Color3d {
+        if self.is_even(p) {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+
+    fn sample(&self, rec: &HitRecord) -> Color3d {
+        if self.is_even(&rec.p) {
+            self.even.sample(rec)
+        } else {
+            self.odd.sample(rec)
+        }
+    }
+}
+
+/// `columns` by `rows` squares over the texture coordinates from 0 to 1,
+/// alternating between `even` and `odd`. It follows the surface, where a
+/// [`CheckerTexture`] cuts through it.
+#[derive(Clone)]
+pub struct UvCheckerTexture<E: Texture, O: Texture> {
+    columns: f64,
+    rows: f64,
+    even: E,
+    odd: O,
+}
+
+impl<E: Texture, O: Texture> UvCheckerTexture<E, O> {
+    pub fn new(columns: f64, rows: f64, even: E, odd: O) -> Self {
+        Self {
+            columns,
+            rows,
+            even,
+            odd,
+        }
+    }
+}
+
+impl UvCheckerTexture<SolidTexture, SolidTexture> {
+    pub fn from_colors(columns: f64, rows: f64, even: Color3d, odd: Color3d) -> Self {
+        Self::new(
+            columns,
+            rows,
+            SolidTexture::new(even),
+            SolidTexture::new(odd),
+        )
+    }
+}
+
+impl<E: Texture, O: Texture> UvCheckerTexture<E, O> {
+    fn is_even(&self, u: f64, v: f64) -> bool {
+        let cell = (u * self.columns).floor() as i64 + (v * self.rows).floor() as i64;
+        cell.rem_euclid(2) == 0
+    }
+}
+
+impl<E: Texture, O: Texture> Texture for UvCheckerTexture<E, O> {
+    fn value(&self, u: f64, v: f64, p: &Point3d) -> Color3d {
+        if self.is_even(u, v) {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+
+    fn sample(&self, rec: &HitRecord) -> Color3d {
+        if self.is_even(rec.u, rec.v) {
+            self.even.sample(rec)
+        } else {
+            self.odd.sample(rec)
+        }
+    }
+}
+
+/// Blends from `low` at 0 to `high` at 1.
+fn blend(low: Color3d, high: Color3d, t: f64) -> Color3d {
+    (1.0 - t) * low + t * high
+}
+
+/// Octaves of turbulence procedural textures add up unless told otherwise.
+pub const DEFAULT_DEPTH: usize = 7;
+
+/// [`Perlin`] noise of features about `1 / scale` apart, from black to
+/// white unless given other colors.
+#[derive(Debug, Clone)]
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f64,
+    low: Color3d,
+    high: Color3d,
+}
+
+impl NoiseTexture {
+    pub fn new(seed: u64, scale: f64) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            low: Color3d::zero(),
+            high: Color3d::only(1.0),
+        }
+    }
+
+    pub fn with_colors(mut self, low: Color3d, high: Color3d) -> Self {
+        self.low = low;
+        self.high = high;
+        self
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3d) -> Color3d {
+        let t = 0.5 * (1.0 + self.noise.noise(&(self.scale * *p)));
+        blend(self.low, self.high, t)
+    }
+}
+
+/// `depth` octaves of [`Perlin::turbulence`], the coarsest about
+/// `1 / scale` apart, from black to white unless given other colors.
+#[derive(Debug, Clone)]
+pub struct TurbulenceTexture {
+    noise: Perlin,
+    scale: f64,
+    depth: usize,
+    low: Color3d,
+    high: Color3d,
+}
+
+impl TurbulenceTexture {
+    pub fn new(seed: u64, scale: f64) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            depth: DEFAULT_DEPTH,
+            low: Color3d::zero(),
+            high: Color3d::only(1.0),
+        }
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_colors(mut self, low: Color3d, high: Color3d) -> Self {
+        self.low = low;
+        self.high = high;
+        self
+    }
+}
+
+impl Texture for TurbulenceTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3d) -> Color3d {
+        let t = self.noise.turbulence(&(self.scale * *p), self.depth);
+        blend(self.low, self.high, t.min(1.0))
+    }
+}
+
+/// Veins along z, `scale` radians of a sine apart and bent by turbulence,
+/// from black to white unless given other colors.
+#[derive(Debug, Clone)]
+pub struct MarbleTexture {
+    noise: Perlin,
+    scale: f64,
+    depth: usize,
+    low: Color3d,
+    high: Color3d,
+}
+
+impl MarbleTexture {
+    pub fn new(seed: u64, scale: f64) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            depth: DEFAULT_DEPTH,
+            low: Color3d::zero(),
+            high: Color3d::only(1.0),
+        }
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_colors(mut self, low: Color3d, high: Color3d) -> Self {
+        self.low = low;
+        self.high = high;
+        self
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3d) -> Color3d {
+        let phase = self.scale * p.z + 10.0 * self.noise.turbulence(p, self.depth);
+        blend(self.low, self.high, 0.5 * (1.0 + phase.sin()))
+    }
+}
+
+/// Growth rings around the y axis, `scale` of them per unit and wobbled by
+/// turbulence, each going from `low` at its inside to `high` at its
+/// outside. Dark and light brown unless given other colors.
+#[derive(Debug, Clone)]
+pub struct WoodTexture {
+    noise: Perlin,
+    scale: f64,
+    depth: usize,
+    low: Color3d,
+    high: Color3d,
+}
+
+impl WoodTexture {
+    pub fn new(seed: u64, scale: f64) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            depth: DEFAULT_DEPTH,
+            low: Color3d::new(0.3, 0.15, 0.05),
+            high: Color3d::new(0.7, 0.45, 0.2),
+        }
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_colors(mut self, low: Color3d, high: Color3d) -> Self {
+        self.low = low;
+        self.high = high;
+        self
+    }
+}
+
+impl Texture for WoodTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3d) -> Color3d {
+        // the grain runs along the trunk, so the noise is stretched along y
+        let grain = Point3d::new(p.x, 0.1 * p.y, p.z);
+        let rings = self.scale * p.x.hypot(p.z) + 2.0 * self.noise.turbulence(&grain, self.depth);
+        blend(self.low, self.high, rings - rings.floor())
+    }
+}
+
+/// Cells around random points about `1 / scale` apart, from `low` at their
+/// points to `high` as far as a unit away from them. Black to white unless
+/// given other colors.
+#[derive(Debug, Clone)]
+pub struct WorleyTexture {
+    noise: Worley,
+    scale: f64,
+    low: Color3d,
+    high: Color3d,
+}
+
+impl WorleyTexture {
+    pub fn new(seed: u64, scale: f64) -> Self {
+        Self {
+            noise: Worley::new(seed),
+            scale,
+            low: Color3d::zero(),
+            high: Color3d::only(1.0),
+        }
+    }
+
+    pub fn with_colors(mut self, low: Color3d, high: Color3d) -> Self {
+        self.low = low;
+        self.high = high;
+        self
+    }
+}
+
+impl Texture for WorleyTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3d) -> Color3d {
+        let t = self.noise.noise(&(self.scale * *p));
+        blend(self.low, self.high, t.min(1.0))
+    }
+}
+
+/// How an [`ImageTexture`] blends the pixels around a lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFilter {
+    /// the closest pixel
+    Nearest,
+    /// the four closest pixels
+    Bilinear,
+    /// the two mip levels whose pixels are about as large as the footprint
+    /// of the ray, bilinearly, so that distant textures do not shimmer
+    Trilinear,
+}
+
+/// What an [`ImageTexture`] shows outside the texture coordinates from 0 to
+/// 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// the image again
+    Repeat,
+    /// the pixels at the edges
+    Clamp,
+    /// the image again, mirrored every other time so that edges meet
+    Mirror,
+}
+
+impl WrapMode {
+    /// Index of the pixel standing in for `i` in a row or column of `n`.
+    fn wrap(self, i: i64, n: usize) -> usize {
+        let n = n as i64;
+        let i = match self {
+            WrapMode::Repeat => i.rem_euclid(n),
+            WrapMode::Clamp => i.clamp(0, n - 1),
+            WrapMode::Mirror => {
+                let i = i.rem_euclid(2 * n);
+                if i < n {
+                    i
+                } else {
+                    2 * n - 1 - i
+                }
+            }
+        };
+        i as usize
+    }
+
+    /// `t` moved into the first period of the wrap, without changing what
+    /// it shows, so that scaling it to pixels cannot overflow.
+    fn coordinate(self, t: f64) -> f64 {
+        match self {
+            WrapMode::Repeat => t.rem_euclid(1.0),
+            WrapMode::Clamp => t.clamp(-1.0, 2.0),
+            WrapMode::Mirror => t.rem_euclid(2.0),
+        }
+    }
+}
+
+/// An image together with its mip levels, each half the size of the one
+/// before down to a single pixel. The levels are only built the first time
+/// they are needed.
+#[derive(Debug)]
+pub struct MipMap {
+    image: Image,
+    levels: OnceLock<Vec<Image>>,
+}
+
+impl MipMap {
+    pub fn new(image: Image) -> Self {
+        assert!(
+            image.width > 0 && image.height > 0 && image.pixels.len() == image.width * image.height,
+            "image texture needs {}x{} pixels, got {}",
+            image.width,
+            image.height,
+            image.pixels.len()
+        );
+        Self {
+            image,
+            levels: OnceLock::new(),
+        }
+    }
+
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Number of levels, the image included.
+    pub fn level_count(&self) -> usize {
+        self.levels().len() + 1
+    }
+
+    /// Level 0 is the image itself.
+    pub fn level(&self, level: usize) -> &Image {
+        match level {
+            0 => &self.image,
+            _ => &self.levels()[level - 1],
+        }
+    }
+
+    fn levels(&self) -> &[Image] {
+        self.levels.get_or_init(|| {
+            let mut levels: Vec<Image> = vec![];
+            let mut last = &self.image;
+            while last.width > 1 || last.height > 1 {
+                levels.push(downsample(last));
+                last = levels.last().unwrap();
+            }
+            levels
+        })
+    }
+}
+
+/// Averages every 2x2 block of pixels, the last row or column of an odd
+/// size counts twice.
+fn downsample(image: &Image) -> Image {
+    let width = (image.width / 2).max(1);
+    let height = (image.height / 2).max(1);
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = Color3d::zero();
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let sx = (2 * x + dx).min(image.width - 1);
+                let sy = (2 * y + dy).min(image.height - 1);
+                sum += image.pixels[sy * image.width + sx];
+            }
+            pixels.push(sum / 4.0);
+        }
+    }
+    Image {
+        width,
+        height,
+        pixels,
+    }
+}
+
+/// Loads every image file once, however many textures show it. Textures of
+/// the same file share its pixels and mip levels, and a file that cannot be
+/// loaded is not tried again.
+#[derive(Debug, Default)]
+pub struct TextureCache {
+    images: Mutex<HashMap<PathBuf, Arc<OnceLock<CachedImage>>>>,
+}
+
+/// io errors cannot be cloned, so a failed load keeps what it said.
+type CachedImage = Result<Arc<MipMap>, (io::ErrorKind, String)>;
+
+impl TextureCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cache [`ImageTexture::load`] goes through. Nothing is ever evicted
+    /// from it, its images stay loaded until the program ends.
+    pub fn global() -> &'static TextureCache {
+        static CACHE: OnceLock<TextureCache> = OnceLock::new();
+        CACHE.get_or_init(TextureCache::new)
+    }
+
+    /// See [`Image::load`]. Different files are loaded in parallel, the same
+    /// file only once.
+    pub fn load(&self, path: impl AsRef<Path>) -> io::Result<Arc<MipMap>> {
+        let path = path.as_ref();
+        // the same file by another name is still the same file
+        let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        // the map is only locked to find the slot of the file, which is
+        // filled without it
+        let slot = self.images().entry(key).or_default().clone();
+        let image = slot.get_or_init(|| {
+            let image = Image::load(path).map_err(|e| (e.kind(), e.to_string()))?;
+            if image.pixels.is_empty() {
+                return Err((io::ErrorKind::InvalidData, "image is empty".to_string()));
+            }
+            Ok(Arc::new(MipMap::new(image)))
+        });
+        image
+            .clone()
+            .map_err(|(kind, message)| io::Error::new(kind, message))
+    }
+
+    /// Entries are inserted whole, so even a map poisoned by a panic is
+    /// fine to use.
+    fn images(&self) -> MutexGuard<'_, HashMap<PathBuf, Arc<OnceLock<CachedImage>>>> {
+        self.images.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Number of images loaded.
+    pub fn len(&self) -> usize {
+        self.images()
+            .values()
+            .filter(|slot| matches!(slot.get(), Some(Ok(_))))
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// An image stretched over the texture coordinates from 0 to 1, `v` going
+/// up from the bottom row, and repeated beyond them. By default it is
+/// filtered trilinearly, which blurs it to what a pixel sees of it when the
+/// ray has a spread and filters it bilinearly when not.
+#[derive(Debug, Clone)]
+pub struct ImageTexture {
+    image: Arc<MipMap>,
+    filter: ImageFilter,
+    wrap: WrapMode,
+}
+
+impl ImageTexture {
+    pub fn new(image: Image) -> Self {
+        Self::shared(Arc::new(MipMap::new(image)))
+    }
+
+    /// A texture of an image other textures may show as well.
+    pub fn shared(image: Arc<MipMap>) -> Self {
+        Self {
+            image,
+            filter: ImageFilter::Trilinear,
+            wrap: WrapMode::Repeat,
+        }
+    }
+
+    /// Loads the image through the [`TextureCache::global`] cache.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        TextureCache::global().load(path).map(Self::shared)
+    }
+
+    pub fn with_filter(mut self, filter: ImageFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: WrapMode) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn image(&self) -> &Arc<MipMap> {
+        &self.image
+    }
+
+    fn pixel(&self, image: &Image, x: i64, y: i64) -> Color3d {
+        let x = self.wrap.wrap(x, image.width);
+        let y = self.wrap.wrap(y, image.height);
+        image.pixels[y * image.width + x]
+    }
+
+    fn nearest(&self, image: &Image, u: f64, v: f64) -> Color3d {
+        let (u, v) = (self.wrap.coordinate(u), self.wrap.coordinate(v));
+        let x = (u * image.width as f64).floor() as i64;
+        let y = ((1.0 - v) * image.height as f64).floor() as i64;
+        self.pixel(image, x, y)
+    }
+
+    fn bilinear(&self, image: &Image, u: f64, v: f64) -> Color3d {
+        let (u, v) = (self.wrap.coordinate(u), self.wrap.coordinate(v));
+        // pixel centers are at half steps
+        let x = u * image.width as f64 - 0.5;
+        let y = (1.0 - v) * image.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = (1.0 - fx) * self.pixel(image, x0, y0) + fx * self.pixel(image, x0 + 1, y0);
+        let bottom =
+            (1.0 - fx) * self.pixel(image, x0, y0 + 1) + fx * self.pixel(image, x0 + 1, y0 + 1);
+        (1.0 - fy) * top + fy * bottom
+    }
+
+    fn trilinear(&self, u: f64, v: f64, width: f64) -> Color3d {
+        let image = self.image.image();
+        // how many pixels of the image the footprint covers
+        let pixels = width * image.width.max(image.height) as f64;
+        if pixels.is_nan() || pixels <= 1.0 {
+            return self.bilinear(image, u, v);
+        }
+        let level = pixels.log2().min((self.image.level_count() - 1) as f64);
+        let below = level.floor();
+        let t = level - below;
+        let below = below as usize;
+        let fine = self.bilinear(self.image.level(below), u, v);
+        if t == 0.0 {
+            return fine;
+        }
+        let coarse = self.bilinear(self.image.level(below + 1), u, v);
+        (1.0 - t) * fine + t * coarse
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3d) -> Color3d {
+        match self.filter {
+            ImageFilter::Nearest => self.nearest(self.image.image(), u, v),
+            _ => self.bilinear(self.image.image(), u, v),
+        }
+    }
+
+    fn sample(&self, rec: &HitRecord) -> Color3d {
+        match self.filter {
+            ImageFilter::Trilinear => self.trilinear(rec.u, rec.v, rec.uv_width),
+            _ => self.value(rec.u, rec.v, &rec.p),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Black and white pixels in a checker.
+    fn checker() -> Image {
+        let (black, white) = (Color3d::zero(), Color3d::only(1.0));
+        Image {
+            width: 2,
+            height: 2,
+            pixels: vec![black, white, white, black],
+        }
+    }
+
+    #[test]
+    fn samples_far_out_of_range_coordinates() {
+        let origin = Point3d::zero();
+        for wrap in [WrapMode::Repeat, WrapMode::Clamp, WrapMode::Mirror] {
+            for filter in [ImageFilter::Nearest, ImageFilter::Bilinear] {
+                let texture = ImageTexture::new(checker())
+                    .with_filter(filter)
+                    .with_wrap(wrap);
+                for (u, v) in [(1e300, 0.5), (0.5, -1e300), (-1e300, 1e300), (1e18, 1e18)] {
+                    let color = texture.value(u, v, &origin);
+                    assert!(
+                        (0.0..=1.0).contains(&color.x),
+                        "{:?} {:?} at ({}, {}) gave {:?}",
+                        wrap,
+                        filter,
+                        u,
+                        v,
+                        color
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn caches_loaded_and_missing_images() {
+        let cache = TextureCache::new();
+        let first = cache.load("scenes/models/bricks.png").unwrap();
+        let again = cache.load("scenes/models/../models/bricks.png").unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        let missing = cache.load("scenes/models/missing.png").unwrap_err();
+        let again = cache.load("scenes/models/missing.png").unwrap_err();
+        assert_eq!(missing.kind(), again.kind());
+        assert_eq!(missing.to_string(), again.to_string());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn wraps_coordinates_by_period() {
+        let origin = Point3d::zero();
+        let value = |wrap, u: f64| {
+            ImageTexture::new(checker())
+                .with_filter(ImageFilter::Nearest)
+                .with_wrap(wrap)
+                .value(u, 0.75, &origin)
+        };
+        assert_eq!(value(WrapMode::Repeat, 3.25), value(WrapMode::Repeat, 0.25));
+        assert_eq!(value(WrapMode::Mirror, 1.25), value(WrapMode::Mirror, 0.75));
+        assert_eq!(value(WrapMode::Clamp, 40.0), value(WrapMode::Clamp, 0.75));
+        assert_eq!(value(WrapMode::Clamp, -40.0), value(WrapMode::Clamp, 0.25));
+    }
+}
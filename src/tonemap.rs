@@ -0,0 +1,178 @@
+use std::{fmt, str::FromStr};
+
+use crate::{utils::clamp, vec3::Color3d};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneMapper {
+    /// clips everything above 1
+    Clamp,
+    /// `c / (1 + c)`
+    Reinhard,
+    /// reinhard that maps the white point to 1
+    ExtendedReinhard,
+    /// Narkowicz's fit of the aces filmic curve
+    Aces,
+    /// John Hable's Uncharted 2 filmic curve
+    Hable,
+    /// the AgX base look, after Troy Sobotka
+    Agx,
+}
+
+impl ToneMapper {
+    pub const NAMES: &'static str = "clamp, reinhard, reinhard-extended, aces, hable or agx";
+}
+
+impl FromStr for ToneMapper {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" | "none" => Ok(ToneMapper::Clamp),
+            "reinhard" => Ok(ToneMapper::Reinhard),
+            "reinhard-extended" | "extended-reinhard" => Ok(ToneMapper::ExtendedReinhard),
+            "aces" => Ok(ToneMapper::Aces),
+            "hable" | "uncharted2" => Ok(ToneMapper::Hable),
+            "agx" => Ok(ToneMapper::Agx),
+            _ => Err(format!(
+                "unknown tone mapper '{}', expected {}",
+                s,
+                ToneMapper::NAMES
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ToneMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ToneMapper::Clamp => "clamp",
+            ToneMapper::Reinhard => "reinhard",
+            ToneMapper::ExtendedReinhard => "reinhard-extended",
+            ToneMapper::Aces => "aces",
+            ToneMapper::Hable => "hable",
+            ToneMapper::Agx => "agx",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How linear radiance is turned into displayable `[0, 1]` values before the
+/// srgb transfer function is applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    pub operator: ToneMapper,
+    /// exposure adjustment in stops, every stop doubles the radiance
+    pub exposure: f64,
+    /// smallest radiance mapped to pure white by the extended reinhard curve
+    pub white_point: f64,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self::new(ToneMapper::Clamp)
+    }
+}
+
+impl ToneMapping {
+    pub fn new(operator: ToneMapper) -> Self {
+        Self {
+            operator,
+            exposure: 0.0,
+            white_point: 4.0,
+        }
+    }
+
+    pub fn with_exposure(mut self, exposure: f64) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    pub fn with_white_point(mut self, white_point: f64) -> Self {
+        self.white_point = white_point;
+        self
+    }
+
+    /// Maps linear radiance to linear display values in `[0, 1]`.
+    pub fn apply(&self, color: Color3d) -> Color3d {
+        let c = color * 2f64.powf(self.exposure);
+        let c = Color3d::new(c.x.max(0.0), c.y.max(0.0), c.z.max(0.0));
+
+        let mapped = match self.operator {
+            ToneMapper::Clamp => c,
+            ToneMapper::Reinhard => map(c, |x| x / (1.0 + x)),
+            ToneMapper::ExtendedReinhard => {
+                let w2 = self.white_point * self.white_point;
+                map(c, |x| x * (1.0 + x / w2) / (1.0 + x))
+            }
+            ToneMapper::Aces => map(c, aces),
+            ToneMapper::Hable => {
+                let white_scale = 1.0 / hable_partial(HABLE_WHITE);
+                // the usual exposure bias of the curve
+                map(c, |x| hable_partial(2.0 * x) * white_scale)
+            }
+            ToneMapper::Agx => agx(c),
+        };
+
+        map(mapped, |x| clamp(x, 0.0, 1.0))
+    }
+}
+
+fn map(c: Color3d, f: impl Fn(f64) -> f64) -> Color3d {
+    Color3d::new(f(c.x), f(c.y), f(c.z))
+}
+
+fn aces(x: f64) -> f64 {
+    // the fit is made for radiance scaled down by 0.6
+    let x = 0.6 * x;
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+const HABLE_WHITE: f64 = 11.2;
+
+fn hable_partial(x: f64) -> f64 {
+    const A: f64 = 0.15;
+    const B: f64 = 0.50;
+    const C: f64 = 0.10;
+    const D: f64 = 0.20;
+    const E: f64 = 0.02;
+    const F: f64 = 0.30;
+    ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F
+}
+
+fn mul(m: &[[f64; 3]; 3], c: Color3d) -> Color3d {
+    Color3d::new(
+        m[0][0] * c.x + m[0][1] * c.y + m[0][2] * c.z,
+        m[1][0] * c.x + m[1][1] * c.y + m[1][2] * c.z,
+        m[2][0] * c.x + m[2][1] * c.y + m[2][2] * c.z,
+    )
+}
+
+// linear srgb into the agx log encoding, and back
+const AGX_INSET: [[f64; 3]; 3] = [
+    [0.842479062253094, 0.0784335999999992, 0.0792237451477643],
+    [0.0423282422610123, 0.878468636469772, 0.0791661274605434],
+    [0.0423756549057051, 0.0784336, 0.879142973793104],
+];
+const AGX_OUTSET: [[f64; 3]; 3] = [
+    [1.19687900512017, -0.0980208811401368, -0.0990297440797205],
+    [-0.0528968517574562, 1.15190312990417, -0.0989611768448433],
+    [-0.0529716355144438, -0.0980434501171241, 1.15107367264116],
+];
+const AGX_MIN_EV: f64 = -12.47393;
+const AGX_MAX_EV: f64 = 4.026069;
+
+fn agx(c: Color3d) -> Color3d {
+    let c = mul(&AGX_INSET, c);
+    let c = map(c, |x| {
+        let ev = clamp(x.max(1e-10).log2(), AGX_MIN_EV, AGX_MAX_EV);
+        let x = (ev - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+
+        // polynomial fit of the default contrast sigmoid
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    });
+    let c = mul(&AGX_OUTSET, c);
+    map(c, |x| x.max(0.0).powf(2.2))
+}
@@ -0,0 +1,121 @@
+//! Any object moved, turned or scaled by a [`Matrix`], or by one that
+//! changes over time.
+//!
+//! Rays are taken into the space of the object and hits back out, so the
+//! object itself never changes. Wrapping an `Arc` of it instead shares the
+//! geometry: a mesh can appear thousands of times while it is stored once.
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    matrix::Matrix,
+    ray::Ray,
+    sampler::Sampler,
+};
+
+pub struct Transformed<H: Hittable> {
+    pub object: H,
+    to_world: Matrix,
+    to_object: Matrix,
+    bbox: Option<Aabb>,
+}
+
+impl<H: Hittable> Transformed<H> {
+    pub fn new(object: H, matrix: Matrix) -> Self {
+        let to_object = matrix
+            .inverse()
+            .expect("the matrix of a transformed object must be invertible");
+        let bbox = object.bounding_box().map(|b| matrix.bounding_box(&b));
+        Self {
+            object,
+            to_world: matrix,
+            to_object,
+            bbox,
+        }
+    }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.to_world
+    }
+}
+
+impl<H: Hittable> Hittable for Transformed<H> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut Sampler) -> Option<HitRecord<'_>> {
+        // the direction is not normalized, so that t stays the same
+        let ray = Ray::new(
+            self.to_object.point(r.origin()),
+            self.to_object.vector(r.direction()),
+            r.time(),
+        )
+        .with_spread(r.spread());
+        let mut rec = self.object.hit(&ray, t_min, t_max, sampler)?;
+        rec.p = self.to_world.point(rec.p);
+        // the normal already faces the ray, and keeps doing so
+        rec.normal = self.to_world.normal(rec.normal).unit_vector();
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+}
+
+/// An object whose matrix blends from `start` at `time0` to `end` at
+/// `time1`, see [`Matrix::lerp`]. Before and after, it stays where it
+/// starts and ends.
+pub struct Animated<H: Hittable> {
+    pub object: H,
+    start: Matrix,
+    end: Matrix,
+    time0: f64,
+    time1: f64,
+    bbox: Option<Aabb>,
+}
+
+impl<H: Hittable> Animated<H> {
+    pub fn new(object: H, start: Matrix, end: Matrix, time0: f64, time1: f64) -> Self {
+        // every point moves in a straight line, so the boxes at both ends
+        // hold the whole path
+        let bbox = object
+            .bounding_box()
+            .map(|b| Aabb::surrounding_box(&start.bounding_box(&b), &end.bounding_box(&b)));
+        Self {
+            object,
+            start,
+            end,
+            time0,
+            time1,
+            bbox,
+        }
+    }
+
+    pub fn matrix(&self, time: f64) -> Matrix {
+        if self.time1 <= self.time0 {
+            return self.start;
+        }
+        let s = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.start.lerp(&self.end, s)
+    }
+}
+
+impl<H: Hittable> Hittable for Animated<H> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, sampler: &mut Sampler) -> Option<HitRecord<'_>> {
+        let to_world = self.matrix(r.time());
+        // an object flattened at this moment is not there
+        let to_object = to_world.inverse()?;
+        let ray = Ray::new(
+            to_object.point(r.origin()),
+            to_object.vector(r.direction()),
+            r.time(),
+        )
+        .with_spread(r.spread());
+        let mut rec = self.object.hit(&ray, t_min, t_max, sampler)?;
+        rec.p = to_world.point(rec.p);
+        rec.normal = to_world.normal(rec.normal).unit_vector();
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+}
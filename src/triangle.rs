@@ -0,0 +1,127 @@
+use std::borrow::Borrow;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    sampler::Sampler,
+    vec3::{Point3d, Vec3d},
+};
+
+/// Möller–Trumbore ray triangle intersection. Returns the distance and the
+/// barycentric weights of `b` and `c` at the hit.
+pub fn intersect_triangle(
+    ray: &Ray,
+    a: Point3d,
+    b: Point3d,
+    c: Point3d,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = ray.direction().cross(&edge2);
+    let det = edge1.dot(&p);
+    // parallel to the plane of the triangle, or the triangle is degenerate
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let s = ray.origin() - a;
+    let u = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let v = ray.direction().dot(&q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(&q) * inv_det;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    Some((t, u, v))
+}
+
+/// How fast the barycentric coordinates change over the triangle, on
+/// average, see [`HitRecord::with_footprint`].
+pub fn barycentric_density(a: Point3d, b: Point3d, c: Point3d) -> f64 {
+    1.0 / (b - a).cross(&(c - a)).length().sqrt()
+}
+
+/// Box around the three corners, padded where it would be flat.
+pub fn triangle_bounding_box(a: Point3d, b: Point3d, c: Point3d) -> Aabb {
+    let mut minimum = Point3d::zero();
+    let mut maximum = Point3d::zero();
+    for axis in 0..3 {
+        minimum[axis] = a[axis].min(b[axis]).min(c[axis]);
+        maximum[axis] = a[axis].max(b[axis]).max(c[axis]);
+        // a flat box would be missed by rays in its plane
+        if maximum[axis] - minimum[axis] < 0.0001 {
+            minimum[axis] -= 0.0001;
+            maximum[axis] += 0.0001;
+        }
+    }
+    Aabb::new(minimum, maximum)
+}
+
+/// A single triangle, the outward normal faces the side from which `a`,
+/// `b` and `c` run counterclockwise.
+#[derive(Clone)]
+pub struct Triangle<M>
+where
+    M: Material,
+{
+    pub a: Point3d,
+    pub b: Point3d,
+    pub c: Point3d,
+    pub material: M,
+}
+
+impl<M: Material> Triangle<M> {
+    pub fn new(a: Point3d, b: Point3d, c: Point3d, m: M) -> Self {
+        Self {
+            a,
+            b,
+            c,
+            material: m,
+        }
+    }
+
+    pub fn normal(&self) -> Vec3d {
+        (self.b - self.a).cross(&(self.c - self.a)).unit_vector()
+    }
+}
+
+impl<M: Material> Hittable for Triangle<M> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        _sampler: &mut Sampler,
+    ) -> Option<HitRecord<'_>> {
+        // the barycentric coordinates of `b` and `c` serve as texture
+        // coordinates
+        intersect_triangle(ray, self.a, self.b, self.c, t_min, t_max).map(|(t, u, v)| {
+            HitRecord::new_with_face_normal(
+                t,
+                ray.at(t),
+                self.normal(),
+                self.material.borrow(),
+                ray,
+            )
+            .with_uv(u, v)
+            .with_footprint(ray, barycentric_density(self.a, self.b, self.c))
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(triangle_bounding_box(self.a, self.b, self.c))
+    }
+}
@@ -2,10 +2,9 @@ use std::{
     f64,
     fmt::Display,
     ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign},
-    process::Output,
 };
 
-use crate::utils::{random_double, random_range};
+use crate::sampler::Sampler;
 
 extern crate num_traits;
 
@@ -107,7 +106,7 @@ impl<T> Index<usize> for Vec3<T> {
         match index {
             0 => &self.x,
             1 => &self.y,
-            2 => &self.y,
+            2 => &self.z,
             _ => panic!("Index {} out of 0 - 2 !", index),
         }
     }
@@ -176,22 +175,26 @@ impl_mul_for! { usize u8 u16 u32 u64 u128 isize i8 i16 i32 i64 i128 f32 f64 }
 
 impl Vec3<f64> {
     #[inline]
-    pub fn random() -> Self {
-        Vec3::new(random_double(), random_double(), random_double())
+    pub fn random(sampler: &mut Sampler) -> Self {
+        Vec3::new(
+            sampler.random_double(),
+            sampler.random_double(),
+            sampler.random_double(),
+        )
     }
 
     #[inline]
-    pub fn random_range(min: f64, max: f64) -> Self {
+    pub fn random_range(sampler: &mut Sampler, min: f64, max: f64) -> Self {
         Vec3::new(
-            random_range(min, max),
-            random_range(min, max),
-            random_range(min, max),
+            sampler.random_range(min, max),
+            sampler.random_range(min, max),
+            sampler.random_range(min, max),
         )
     }
 
-    pub fn random_in_unit_sphere() -> Self {
+    pub fn random_in_unit_sphere(sampler: &mut Sampler) -> Self {
         loop {
-            let p = Vec3d::random_range(-1.0, 1.0);
+            let p = Vec3d::random_range(sampler, -1.0, 1.0);
             if p.length_squared() >= 1.0 {
                 continue;
             }
@@ -199,12 +202,12 @@ impl Vec3<f64> {
         }
     }
 
-    pub fn random_unit_vector() -> Self {
-        Vec3::random_in_unit_sphere().unit_vector()
+    pub fn random_unit_vector(sampler: &mut Sampler) -> Self {
+        Vec3::random_in_unit_sphere(sampler).unit_vector()
     }
 
-    pub fn random_in_hemisphere(normal: &Vec3d) -> Self {
-        let in_unit_sphere = Vec3d::random_in_unit_sphere();
+    pub fn random_in_hemisphere(sampler: &mut Sampler, normal: &Vec3d) -> Self {
+        let in_unit_sphere = Vec3d::random_in_unit_sphere(sampler);
         if in_unit_sphere.dot(normal) > 0.0 {
             in_unit_sphere
         } else {
@@ -228,9 +231,13 @@ impl Vec3<f64> {
         r_out_parallel + r_out_perp
     }
 
-    pub fn random_in_unit_disk() -> Vec3d {
+    pub fn random_in_unit_disk(sampler: &mut Sampler) -> Vec3d {
         loop {
-            let p = Vec3d::new(random_range(-1.0, 1.0), random_range(-1.0, 1.0), 0.0);
+            let p = Vec3d::new(
+                sampler.random_range(-1.0, 1.0),
+                sampler.random_range(-1.0, 1.0),
+                0.0,
+            );
             if p.length_squared() >= 1.0 {
                 continue;
             }